@@ -1,17 +1,23 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
+use chrono::{DateTime, NaiveDate, Utc};
 use rmcp::{
-    handler::server::{router::tool::ToolRouter, wrapper::Parameters, ServerHandler},
+    handler::server::{
+        router::{prompt::PromptRouter, tool::ToolRouter},
+        wrapper::Parameters,
+        ServerHandler,
+    },
     model::{ErrorData as McpError, *},
-    schemars, tool, tool_handler, tool_router,
+    prompt, prompt_handler, prompt_router, schemars,
+    service::RequestContext,
+    tool, tool_handler, tool_router,
     transport::stdio,
-    ServiceExt,
+    Peer, RoleServer, ServiceExt,
 };
 use serde::{Deserialize, Serialize};
-use serde_json;
 
 /// Deliberate thinking request parameters
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
@@ -62,6 +68,13 @@ pub struct DeliberateThinkingRequest {
     )]
     #[schemars(description = "Backlog stories to add or update")]
     pub backlog_stories: Vec<BacklogItem>,
+    #[serde(
+        rename = "backlogPatches",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Partial updates to existing stories; omitted fields are kept")]
+    pub backlog_patches: Vec<BacklogPatch>,
     #[serde(
         rename = "removeStoryIds",
         default,
@@ -69,20 +82,77 @@ pub struct DeliberateThinkingRequest {
     )]
     #[schemars(description = "Backlog story identifiers slated for removal")]
     pub remove_story_ids: Vec<String>,
+    #[serde(rename = "removeChildren", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "What happens to the children of removed items")]
+    pub remove_children: Option<ChildRemovalMode>,
+    #[serde(rename = "backlogView", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Render the backlog flat (default) or grouped by epic")]
+    pub backlog_view: Option<BacklogView>,
+    #[serde(rename = "backlogFilter", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Report the matching items as filteredBacklog in the PM report")]
+    pub backlog_filter: Option<BacklogFilter>,
     #[serde(rename = "sprintPlan", skip_serializing_if = "Option::is_none")]
-    #[schemars(description = "Sprint plan proposal from the project manager")]
+    #[schemars(
+        description = "Sprint plan proposal from the project manager; amends the active sprint or starts one when none is active"
+    )]
     pub sprint_plan: Option<SprintPlan>,
+    #[serde(rename = "startSprint", skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Explicitly start a new sprint; fails while another sprint is active"
+    )]
+    pub start_sprint: Option<SprintPlan>,
+    #[serde(rename = "closeSprint", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Close the active sprint and archive it in the sprint history")]
+    pub close_sprint: Option<SprintClosure>,
+    #[serde(
+        rename = "retrospective",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Retrospective entries for the most recently closed sprint")]
+    pub retrospective: Vec<RetroEntry>,
+    #[serde(
+        rename = "resolveRetroActionIds",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Retrospective action items that are now complete")]
+    pub resolve_retro_action_ids: Vec<String>,
     #[serde(rename = "consensusUpdate", skip_serializing_if = "Option::is_none")]
     #[schemars(description = "Consensus status update for the iteration")]
     pub consensus_update: Option<ConsensusUpdate>,
     #[serde(rename = "requiresUserInput", skip_serializing_if = "Option::is_none")]
-    #[schemars(description = "Whether the team needs user input before proceeding")]
+    #[schemars(
+        description = "true queues this thought as a blocking question unless one is open; false withdraws the open blocking questions"
+    )]
     pub requires_user_input: Option<bool>,
+    #[serde(rename = "definitionOfDone", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Replacement team-wide definition-of-done checklist")]
+    pub definition_of_done: Option<Vec<String>>,
+    #[serde(rename = "prioritization", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Scheme used to rank the backlog: priority, rice or wsjf")]
+    pub prioritization: Option<PrioritizationScheme>,
+    #[serde(rename = "decisions", default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Decisions reached in this thought, recorded in the decision log")]
+    pub decisions: Vec<DecisionInput>,
+    #[serde(rename = "risks", default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "New risks (no id) or updates to registered risks (with id)")]
+    pub risks: Vec<RiskUpdate>,
+    #[serde(rename = "blockers", default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(
+        description = "New blockers (no id), or changes to existing ones: set status to resolved or open to resolve or reopen"
+    )]
+    pub blockers: Vec<BlockerUpdate>,
+    #[serde(rename = "questions", default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(
+        description = "Questions for the user; blocking ones keep the team waiting until answered with the answerquestion tool"
+    )]
+    pub questions: Vec<QuestionInput>,
 }
 
 impl DeliberateThinkingRequest {
-    /// Validates the request parameters
-    fn validate(&self) -> Result<(), McpError> {
+    /// Validates the request parameters against the team permission matrix
+    fn validate(&self, permissions: &PermissionMatrix) -> Result<(), McpError> {
         validate_min_value("thoughtNumber", self.thought_number, 1)?;
         validate_min_value("totalThoughts", self.total_thoughts, 1)?;
 
@@ -103,12 +173,29 @@ impl DeliberateThinkingRequest {
         }
 
         for point in &self.discussion_points {
+            if point.role == Speaker::User {
+                return Err(create_validation_error(
+                    "discussionPoints.role: user is reserved for answers recorded with answerquestion",
+                ));
+            }
             validate_non_empty("discussionPoints.detail", &point.detail)?;
         }
 
         for story in &self.backlog_stories {
-            validate_non_empty("backlogStories.id", &story.id)?;
-            validate_non_empty("backlogStories.title", &story.title)?;
+            story.validate_fields("backlogStories")?;
+        }
+
+        if let Some(checklist) = &self.definition_of_done {
+            for item in checklist {
+                validate_non_empty("definitionOfDone[]", item)?;
+            }
+        }
+
+        for patch in &self.backlog_patches {
+            validate_non_empty("backlogPatches.id", &patch.id)?;
+            if let Some(title) = &patch.title {
+                validate_non_empty("backlogPatches.title", title)?;
+            }
         }
 
         for story_id in &self.remove_story_ids {
@@ -121,6 +208,147 @@ impl DeliberateThinkingRequest {
             validate_min_value("sprintPlan.durationDays", plan.duration_days, 1)?;
         }
 
+        if let Some(plan) = &self.start_sprint {
+            if self.sprint_plan.is_some() {
+                return Err(create_validation_error(
+                    "sprintPlan and startSprint cannot be sent together",
+                ));
+            }
+            validate_non_empty("startSprint.sprintName", &plan.sprint_name)?;
+            validate_non_empty("startSprint.goal", &plan.goal)?;
+            validate_min_value("startSprint.durationDays", plan.duration_days, 1)?;
+        }
+
+        for entry in &self.retrospective {
+            for action in &entry.action_items {
+                validate_non_empty("retrospective.actionItems.description", &action.description)?;
+                if let Some(story_id) = &action.story_id {
+                    validate_non_empty("retrospective.actionItems.storyId", story_id)?;
+                }
+            }
+        }
+
+        for decision in &self.decisions {
+            validate_non_empty("decisions.title", &decision.title)?;
+            validate_non_empty("decisions.context", &decision.context)?;
+            validate_non_empty("decisions.chosenOption", &decision.chosen_option)?;
+            for option in &decision.options {
+                validate_non_empty("decisions.options[]", option)?;
+            }
+            if !decision.options.is_empty() && !decision.options.contains(&decision.chosen_option) {
+                return Err(create_validation_error(&format!(
+                    "decisions.chosenOption '{}' is not one of the options considered",
+                    decision.chosen_option
+                )));
+            }
+        }
+
+        for risk in &self.risks {
+            match &risk.id {
+                Some(id) => validate_non_empty("risks.id", id)?,
+                None => {
+                    let description = risk.description.as_deref().unwrap_or_default();
+                    validate_non_empty("risks.description", description)?;
+                }
+            }
+            if let Some(description) = &risk.description {
+                validate_non_empty("risks.description", description)?;
+            }
+        }
+
+        for blocker in &self.blockers {
+            match &blocker.id {
+                Some(id) => validate_non_empty("blockers.id", id)?,
+                None => {
+                    let description = blocker.description.as_deref().unwrap_or_default();
+                    validate_non_empty("blockers.description", description)?;
+                    if blocker.status == Some(BlockerStatus::Resolved) {
+                        return Err(create_validation_error(
+                            "blockers: a new blocker cannot start out resolved",
+                        ));
+                    }
+                }
+            }
+            if let Some(description) = &blocker.description {
+                validate_non_empty("blockers.description", description)?;
+            }
+        }
+
+        for question in &self.questions {
+            validate_non_empty("questions.question", &question.question)?;
+            for option in &question.options {
+                validate_non_empty("questions.options[]", option)?;
+            }
+        }
+
+        for action_id in &self.resolve_retro_action_ids {
+            validate_non_empty("resolveRetroActionIds[]", action_id)?;
+        }
+
+        self.validate_permissions(permissions)
+    }
+
+    /// Rejects team state mutations the requesting role is not allowed to make
+    fn validate_permissions(&self, permissions: &PermissionMatrix) -> Result<(), McpError> {
+        let role = self.role.as_ref();
+
+        if !self.backlog_stories.is_empty() || !self.backlog_patches.is_empty() {
+            permissions.check(TeamAction::AddStories, role)?;
+        }
+
+        if !self.remove_story_ids.is_empty() {
+            permissions.check(TeamAction::RemoveStories, role)?;
+        }
+
+        if self.sprint_plan.is_some() || self.start_sprint.is_some() || self.close_sprint.is_some()
+        {
+            permissions.check(TeamAction::SetSprint, role)?;
+        }
+
+        if let Some(update) = &self.consensus_update {
+            permissions.check(TeamAction::CastVote, role)?;
+            if !update.blockers.is_empty() || update.notes.is_some() {
+                permissions.check(TeamAction::UpdateConsensus, role)?;
+            }
+        }
+
+        if self.requires_user_input.is_some() || !self.questions.is_empty() {
+            permissions.check(TeamAction::ToggleUserInput, role)?;
+        }
+
+        if self.definition_of_done.is_some() {
+            permissions.check(TeamAction::SetDefinitionOfDone, role)?;
+        }
+
+        if self.prioritization.is_some() {
+            permissions.check(TeamAction::SetPrioritization, role)?;
+        }
+
+        if !self.retrospective.is_empty() || !self.resolve_retro_action_ids.is_empty() {
+            permissions.check(TeamAction::Retrospective, role)?;
+        }
+
+        if !self.decisions.is_empty() {
+            permissions.check(TeamAction::RecordDecision, role)?;
+        }
+
+        if !self.risks.is_empty() {
+            permissions.check(TeamAction::ManageRisks, role)?;
+        }
+
+        if !self.blockers.is_empty() {
+            permissions.check(TeamAction::ManageBlockers, role)?;
+        }
+
+        let creates_stories = self
+            .retrospective
+            .iter()
+            .flat_map(|entry| &entry.action_items)
+            .any(|action| action.story_id.is_some());
+        if creates_stories {
+            permissions.check(TeamAction::AddStories, role)?;
+        }
+
         Ok(())
     }
 }
@@ -208,19 +436,236 @@ impl fmt::Display for TeamRole {
     }
 }
 
+impl TeamRole {
+    fn all() -> Vec<TeamRole> {
+        vec![
+            TeamRole::ProjectManager,
+            TeamRole::PragmaticProgrammer,
+            TeamRole::ProductVisionary,
+        ]
+    }
+}
+
+/// Team state mutations that can be restricted to specific roles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TeamAction {
+    AddStories,
+    RemoveStories,
+    SetSprint,
+    CastVote,
+    UpdateConsensus,
+    ToggleUserInput,
+    SetDefinitionOfDone,
+    SetPrioritization,
+    Retrospective,
+    RecordDecision,
+    ManageRisks,
+    ManageBlockers,
+}
+
+impl TeamAction {
+    /// Request field that triggers this action, used in error messages
+    fn field_name(&self) -> &'static str {
+        match self {
+            TeamAction::AddStories => "backlogStories/backlogPatches",
+            TeamAction::RemoveStories => "removeStoryIds",
+            TeamAction::SetSprint => "sprintPlan/startSprint/closeSprint",
+            TeamAction::CastVote => "consensusUpdate",
+            TeamAction::UpdateConsensus => "consensusUpdate.blockers/notes",
+            TeamAction::ToggleUserInput => "requiresUserInput/questions",
+            TeamAction::SetDefinitionOfDone => "definitionOfDone",
+            TeamAction::SetPrioritization => "prioritization",
+            TeamAction::Retrospective => "retrospective/resolveRetroActionIds",
+            TeamAction::RecordDecision => "decisions",
+            TeamAction::ManageRisks => "risks",
+            TeamAction::ManageBlockers => "blockers",
+        }
+    }
+}
+
+/// Which roles may perform each team state mutation
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PermissionMatrix {
+    #[schemars(description = "Roles allowed to add or update backlog stories")]
+    pub add_stories: Vec<TeamRole>,
+    #[schemars(description = "Roles allowed to remove backlog stories")]
+    pub remove_stories: Vec<TeamRole>,
+    #[schemars(description = "Roles allowed to plan, start and close sprints")]
+    pub set_sprint: Vec<TeamRole>,
+    #[schemars(description = "Roles allowed to cast a consensus vote")]
+    pub cast_vote: Vec<TeamRole>,
+    #[schemars(description = "Roles allowed to change consensus blockers and notes")]
+    pub update_consensus: Vec<TeamRole>,
+    #[schemars(
+        description = "Roles allowed to toggle requiresUserInput and ask the user questions"
+    )]
+    pub toggle_user_input: Vec<TeamRole>,
+    #[schemars(description = "Roles allowed to replace the definition of done")]
+    pub set_definition_of_done: Vec<TeamRole>,
+    #[schemars(description = "Roles allowed to switch the backlog prioritization scheme")]
+    pub set_prioritization: Vec<TeamRole>,
+    #[schemars(
+        description = "Roles allowed to add retrospective entries and resolve retro actions"
+    )]
+    pub retrospective: Vec<TeamRole>,
+    #[schemars(description = "Roles allowed to record and supersede decisions")]
+    pub record_decision: Vec<TeamRole>,
+    #[schemars(description = "Roles allowed to add and update risks in the risk register")]
+    pub manage_risks: Vec<TeamRole>,
+    #[schemars(description = "Roles allowed to raise, resolve and reopen blockers")]
+    pub manage_blockers: Vec<TeamRole>,
+}
+
+impl Default for PermissionMatrix {
+    fn default() -> Self {
+        Self {
+            add_stories: TeamRole::all(),
+            remove_stories: vec![TeamRole::ProjectManager],
+            set_sprint: vec![TeamRole::ProjectManager],
+            cast_vote: TeamRole::all(),
+            update_consensus: vec![TeamRole::ProjectManager],
+            toggle_user_input: TeamRole::all(),
+            set_definition_of_done: vec![TeamRole::ProjectManager],
+            set_prioritization: vec![TeamRole::ProjectManager, TeamRole::ProductVisionary],
+            retrospective: TeamRole::all(),
+            record_decision: TeamRole::all(),
+            manage_risks: TeamRole::all(),
+            manage_blockers: vec![TeamRole::ProjectManager],
+        }
+    }
+}
+
+impl PermissionMatrix {
+    fn allowed_roles(&self, action: TeamAction) -> &[TeamRole] {
+        match action {
+            TeamAction::AddStories => &self.add_stories,
+            TeamAction::RemoveStories => &self.remove_stories,
+            TeamAction::SetSprint => &self.set_sprint,
+            TeamAction::CastVote => &self.cast_vote,
+            TeamAction::UpdateConsensus => &self.update_consensus,
+            TeamAction::ToggleUserInput => &self.toggle_user_input,
+            TeamAction::SetDefinitionOfDone => &self.set_definition_of_done,
+            TeamAction::SetPrioritization => &self.set_prioritization,
+            TeamAction::Retrospective => &self.retrospective,
+            TeamAction::RecordDecision => &self.record_decision,
+            TeamAction::ManageRisks => &self.manage_risks,
+            TeamAction::ManageBlockers => &self.manage_blockers,
+        }
+    }
+
+    /// Checks that the role may perform the action, naming the required role otherwise
+    fn check(&self, action: TeamAction, role: Option<&TeamRole>) -> Result<(), McpError> {
+        let allowed = self.allowed_roles(action);
+        if role.is_some_and(|role| allowed.contains(role)) {
+            return Ok(());
+        }
+
+        if allowed.is_empty() {
+            return Err(create_validation_error(&format!(
+                "{} is disabled by the team permission matrix",
+                action.field_name()
+            )));
+        }
+
+        let required = allowed
+            .iter()
+            .map(|role| role.to_string())
+            .collect::<Vec<_>>()
+            .join(" or ");
+        let requester = role
+            .map(|role| role.to_string())
+            .unwrap_or_else(|| "no role".to_string());
+        Err(create_validation_error(&format!(
+            "{} requires role {} (request sent by {})",
+            action.field_name(),
+            required,
+            requester
+        )))
+    }
+}
+
+/// Server-wide team configuration loaded at start-up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TeamConfig {
+    pub permissions: PermissionMatrix,
+    pub quorum: QuorumRule,
+    pub definition_of_done: Vec<String>,
+    pub prioritization: PrioritizationScheme,
+    pub workflow: WorkflowConfig,
+    pub burndown_granularity: BurndownGranularity,
+    /// Number of closed sprints averaged into the rolling velocity
+    pub velocity_window: usize,
+    /// Also write the per-thought log lines to stderr through env_logger
+    pub stderr_logging: bool,
+}
+
+impl Default for TeamConfig {
+    fn default() -> Self {
+        Self {
+            permissions: PermissionMatrix::default(),
+            quorum: QuorumRule::default(),
+            definition_of_done: Vec::new(),
+            prioritization: PrioritizationScheme::default(),
+            workflow: WorkflowConfig::default(),
+            burndown_granularity: BurndownGranularity::default(),
+            velocity_window: 3,
+            stderr_logging: false,
+        }
+    }
+}
+
+impl TeamConfig {
+    /// Loads the configuration from the JSON file named by `DELIBERATE_THINKING_CONFIG`
+    fn from_env() -> Result<Self, Box<dyn std::error::Error>> {
+        match std::env::var_os(CONFIG_ENV_VAR) {
+            Some(path) => {
+                let contents = std::fs::read_to_string(&path)?;
+                Ok(serde_json::from_str(&contents)?)
+            }
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+const CONFIG_ENV_VAR: &str = "DELIBERATE_THINKING_CONFIG";
+
+/// Who raised a discussion point: a team role, or the user answering a question
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Speaker {
+    User,
+    #[serde(untagged)]
+    Role(TeamRole),
+}
+
+impl fmt::Display for Speaker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Speaker::User => write!(f, "User"),
+            Speaker::Role(role) => write!(f, "{}", role),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct DiscussionPoint {
-    #[schemars(description = "Team role that raised this point")]
-    pub role: TeamRole,
+    #[schemars(description = "Team role that raised this point; user marks the user's answers")]
+    pub role: Speaker,
     #[schemars(description = "Summary of the discussion item")]
     pub detail: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Question for the user this point answers or refers to")]
+    pub question_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum PriorityLevel {
     High,
+    #[default]
     Medium,
     Low,
 }
@@ -245,9 +690,10 @@ impl fmt::Display for PriorityLevel {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub enum StoryStatus {
+    #[default]
     Todo,
     InProgress,
     Blocked,
@@ -263,6 +709,24 @@ impl StoryStatus {
             StoryStatus::Done => 3,
         }
     }
+
+    /// How far along the delivery flow a status is; moving backwards is a regression
+    fn progress(&self) -> u8 {
+        match self {
+            StoryStatus::Todo => 0,
+            StoryStatus::InProgress | StoryStatus::Blocked => 1,
+            StoryStatus::Done => 2,
+        }
+    }
+
+    fn all() -> Vec<StoryStatus> {
+        vec![
+            StoryStatus::Todo,
+            StoryStatus::InProgress,
+            StoryStatus::Blocked,
+            StoryStatus::Done,
+        ]
+    }
 }
 
 impl fmt::Display for StoryStatus {
@@ -276,8 +740,119 @@ impl fmt::Display for StoryStatus {
     }
 }
 
+/// An allowed move between two story statuses
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusTransition {
+    pub from: StoryStatus,
+    pub to: StoryStatus,
+    #[serde(default)]
+    #[schemars(description = "Whether the story must carry a statusReason for this move")]
+    pub requires_reason: bool,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkflowEnforcement {
+    /// Refuse requests that break the workflow
+    #[default]
+    Reject,
+    /// Apply the change and report a warning
+    Warn,
+}
+
+/// Story status transition graph
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkflowConfig {
+    #[schemars(description = "Allowed status transitions; anything else is invalid")]
+    pub transitions: Vec<StatusTransition>,
+    #[schemars(description = "Whether invalid transitions are rejected or only warned about")]
+    pub enforcement: WorkflowEnforcement,
+}
+
+impl Default for WorkflowConfig {
+    /// Every move is allowed, but blocking a story or reopening a Done one needs a reason
+    fn default() -> Self {
+        let mut transitions = Vec::new();
+        for from in StoryStatus::all() {
+            for to in StoryStatus::all() {
+                if from == to {
+                    continue;
+                }
+                let requires_reason = to == StoryStatus::Blocked || from == StoryStatus::Done;
+                transitions.push(StatusTransition {
+                    from: from.clone(),
+                    to,
+                    requires_reason,
+                });
+            }
+        }
+        Self {
+            transitions,
+            enforcement: WorkflowEnforcement::default(),
+        }
+    }
+}
+
+impl WorkflowConfig {
+    /// Describes why moving `story` from `from` to its current status breaks the workflow
+    fn violation(&self, from: &StoryStatus, story: &BacklogItem) -> Option<String> {
+        if from == &story.status {
+            return None;
+        }
+
+        let Some(transition) = self
+            .transitions
+            .iter()
+            .find(|transition| &transition.from == from && transition.to == story.status)
+        else {
+            return Some(format!(
+                "{} cannot move {} -> {}: transition not allowed by the workflow",
+                story.id, from, story.status
+            ));
+        };
+
+        let has_reason = story
+            .status_reason
+            .as_deref()
+            .is_some_and(|reason| !reason.trim().is_empty());
+        if transition.requires_reason && !has_reason {
+            return Some(format!(
+                "{} cannot move {} -> {} without a statusReason",
+                story.id, from, story.status
+            ));
+        }
+
+        None
+    }
+}
+
+/// A story that moved backwards in the delivery flow
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
+pub struct StatusRegression {
+    pub story_id: String,
+    pub from: StoryStatus,
+    pub to: StoryStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl fmt::Display for StatusRegression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} -> {}", self.story_id, self.from, self.to)?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({})", reason)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct BacklogItem {
     #[schemars(description = "Unique identifier for the story")]
     pub id: String,
@@ -287,678 +862,5861 @@ pub struct BacklogItem {
     pub priority: PriorityLevel,
     #[schemars(description = "Current delivery status")]
     pub status: StoryStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Why the story moved to its current status")]
+    pub status_reason: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schemars(description = "Team role currently accountable for the story")]
     pub owner: Option<TeamRole>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[schemars(description = "Additional implementation notes")]
     pub notes: Option<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct SprintParticipant {
-    #[schemars(description = "Role assigned to this sprint")]
-    pub role: TeamRole,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Identifiers of stories that must be finished before this one")]
+    pub depends_on: Vec<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
-    #[schemars(description = "Reason this role is included in the sprint")]
-    pub reasoning: Option<String>,
+    #[schemars(description = "Relative size estimate in story points")]
+    pub story_points: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Value and effort inputs for RICE or WSJF scoring")]
+    pub scoring: Option<ScoringInputs>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    #[schemars(description = "Key responsibilities for this role")]
-    pub responsibilities: Vec<String>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct SprintPlan {
-    #[schemars(description = "Sprint name or identifier")]
-    pub sprint_name: String,
-    #[schemars(description = "Goal for the sprint")]
-    pub goal: String,
-    #[schemars(description = "Planned duration of the sprint in days", range(min = 1))]
-    pub duration_days: u32,
+    #[schemars(description = "Conditions that must be checked off before the story is Done")]
+    pub acceptance_criteria: Vec<AcceptanceCriterion>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    #[schemars(description = "Participants for the sprint with rationale")]
-    pub participants: Vec<SprintParticipant>,
+    #[schemars(description = "Definition-of-done items confirmed for this story")]
+    pub done_checklist: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Epic or parent item this story belongs to")]
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    #[schemars(description = "Kind of work item")]
+    pub item_type: ItemType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Bug severity (bugs only)")]
+    pub severity: Option<BugSeverity>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    #[schemars(description = "Backlog stories committed to this sprint")]
-    pub committed_story_ids: Vec<String>,
+    #[schemars(description = "Steps to reproduce the bug (bugs only)")]
+    pub reproduction_steps: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Thoughts the spike may stay In Progress before it is flagged (spikes only)"
+    )]
+    pub timebox_thoughts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "What the spike should answer (spikes only)")]
+    pub expected_outcome: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    #[schemars(description = "Known risks tracked by the project manager")]
-    pub risks: Vec<String>,
+    #[schemars(description = "Free-form labels such as security or onboarding")]
+    pub labels: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct ConsensusUpdate {
-    #[schemars(description = "Whether the team agrees code changes are ready")]
-    pub ready_for_code_changes: bool,
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    #[schemars(description = "Outstanding blockers identified by the team")]
-    pub blockers: Vec<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    #[schemars(description = "Additional notes from the project manager")]
-    pub notes: Option<String>,
-}
+impl BacklogItem {
+    /// Rejects type-specific fields set on the wrong kind of item
+    fn validate_type_fields(&self) -> Result<(), McpError> {
+        let misplaced = |field: &str, item_type: ItemType| {
+            create_validation_error(&format!(
+                "{}: {} is only valid for {} items",
+                self.id, field, item_type
+            ))
+        };
 
-#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
-#[serde(rename_all = "camelCase")]
-pub struct ConsensusState {
-    pub ready_for_code_changes: bool,
-    #[serde(default)]
-    pub blockers: Vec<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub notes: Option<String>,
-}
+        if self.item_type != ItemType::Bug {
+            if self.severity.is_some() {
+                return Err(misplaced("severity", ItemType::Bug));
+            }
+            if !self.reproduction_steps.is_empty() {
+                return Err(misplaced("reproductionSteps", ItemType::Bug));
+            }
+        }
 
-impl Default for ConsensusState {
-    fn default() -> Self {
-        Self {
-            ready_for_code_changes: false,
-            blockers: Vec::new(),
-            notes: None,
+        if self.item_type != ItemType::Spike {
+            if self.timebox_thoughts.is_some() {
+                return Err(misplaced("timeboxThoughts", ItemType::Spike));
+            }
+            if self.expected_outcome.is_some() {
+                return Err(misplaced("expectedOutcome", ItemType::Spike));
+            }
         }
-    }
-}
 
-#[derive(Debug, Clone)]
-struct BacklogChange {
-    change_type: BacklogChangeType,
-    item: BacklogItem,
-}
+        if let Some(timebox) = self.timebox_thoughts {
+            validate_min_value("timeboxThoughts", timebox, 1)?;
+        }
 
-impl BacklogChange {
-    fn new(change_type: BacklogChangeType, item: BacklogItem) -> Self {
-        Self { change_type, item }
+        Ok(())
     }
 
-    fn summary(&self) -> String {
-        match self.change_type {
-            BacklogChangeType::Added => format!(
-                "Added {} [{} | {}]",
-                self.item.id, self.item.priority, self.item.status
-            ),
-            BacklogChangeType::Updated => format!(
-                "Updated {} -> {} [{}]",
-                self.item.id, self.item.status, self.item.priority
-            ),
-            BacklogChangeType::Removed => {
-                format!("Removed {} ({})", self.item.id, self.item.title)
+    /// Value score under the scheme, or `None` when the inputs are missing
+    fn value_score(&self, scheme: PrioritizationScheme) -> Option<f64> {
+        let scoring = self.scoring.as_ref()?;
+        let size = |explicit: Option<f64>| {
+            explicit
+                .or(self.story_points.map(f64::from))
+                .filter(|size| *size > 0.0)
+        };
+
+        match scheme {
+            PrioritizationScheme::Priority => None,
+            PrioritizationScheme::Rice => {
+                let reach = scoring.reach?;
+                let impact = scoring.impact?;
+                let confidence = scoring.confidence?;
+                Some(reach * impact * (confidence / 100.0) / size(scoring.effort)?)
             }
+            PrioritizationScheme::Wsjf => {
+                let components = [
+                    scoring.business_value,
+                    scoring.time_criticality,
+                    scoring.risk_reduction,
+                ];
+                if components.iter().all(Option::is_none) {
+                    return None;
+                }
+                let cost_of_delay: f64 = components.iter().flatten().sum();
+                Some(cost_of_delay / size(scoring.job_size)?)
+            }
+        }
+    }
+
+    /// Rejects blank text fields, naming them under `source`
+    fn validate_fields(&self, source: &str) -> Result<(), McpError> {
+        validate_non_empty(&format!("{}.id", source), &self.id)?;
+        validate_non_empty(&format!("{}.title", source), &self.title)?;
+        for dependency in &self.depends_on {
+            validate_non_empty(&format!("{}.dependsOn[]", source), dependency)?;
+        }
+        for criterion in &self.acceptance_criteria {
+            validate_non_empty(
+                &format!("{}.acceptanceCriteria.description", source),
+                &criterion.description,
+            )?;
+        }
+        for label in &self.labels {
+            validate_non_empty(&format!("{}.labels[]", source), label)?;
+        }
+        Ok(())
+    }
+
+    /// Lists what still stands between this story and Done
+    fn done_gaps(&self, definition_of_done: &[String]) -> Vec<String> {
+        let mut gaps = Vec::new();
+
+        let unchecked = self
+            .acceptance_criteria
+            .iter()
+            .filter(|criterion| !criterion.checked)
+            .map(|criterion| criterion.description.as_str())
+            .collect::<Vec<_>>();
+        if !unchecked.is_empty() {
+            gaps.push(format!(
+                "unchecked acceptance criteria: {}",
+                unchecked.join(", ")
+            ));
+        }
+
+        let unconfirmed = definition_of_done
+            .iter()
+            .filter(|item| !self.done_checklist.contains(item))
+            .map(|item| item.as_str())
+            .collect::<Vec<_>>();
+        if !unconfirmed.is_empty() {
+            gaps.push(format!(
+                "definition of done not confirmed: {}",
+                unconfirmed.join(", ")
+            ));
         }
+
+        gaps
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum BacklogChangeType {
-    Added,
-    Updated,
-    Removed,
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq, Hash,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ItemType {
+    #[default]
+    Story,
+    Bug,
+    Spike,
+    Chore,
 }
 
-#[derive(Debug, Default)]
-struct TeamUpdateOutcome {
-    pm_summary: Option<String>,
-    new_discussion_points: Vec<DiscussionPoint>,
-    backlog_changes: Vec<BacklogChange>,
-    sprint_plan_updated: Option<SprintPlan>,
-    consensus_state: Option<ConsensusState>,
-    awaiting_user_input: Option<bool>,
+impl ItemType {
+    fn all() -> Vec<ItemType> {
+        vec![
+            ItemType::Story,
+            ItemType::Bug,
+            ItemType::Spike,
+            ItemType::Chore,
+        ]
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+impl fmt::Display for ItemType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ItemType::Story => write!(f, "Story"),
+            ItemType::Bug => write!(f, "Bug"),
+            ItemType::Spike => write!(f, "Spike"),
+            ItemType::Chore => write!(f, "Chore"),
+        }
+    }
+}
+
+/// Criteria for narrowing the backlog; every supplied criterion must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "camelCase")]
-pub struct ProjectManagerReport {
-    #[schemars(description = "Structured bullet points shared with the user")]
-    pub bullets: Vec<String>,
-    #[serde(rename = "pmSummary", skip_serializing_if = "Option::is_none")]
-    #[schemars(description = "Latest project manager summary narrative")]
-    pub pm_summary: Option<String>,
-    #[serde(
-        rename = "newDiscussionPoints",
-        default,
-        skip_serializing_if = "Vec::is_empty"
-    )]
-    #[schemars(description = "Discussion points captured during this iteration")]
-    pub new_discussion_points: Vec<DiscussionPoint>,
-    #[serde(
-        rename = "backlogSnapshot",
-        default,
-        skip_serializing_if = "Vec::is_empty"
+pub struct BacklogFilter {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Labels the item must all carry (case-insensitive)")]
+    pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Role that must own the item")]
+    pub owner: Option<TeamRole>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Statuses to include")]
+    pub status: Vec<StoryStatus>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Priorities to include")]
+    pub priority: Vec<PriorityLevel>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Item types to include")]
+    pub item_type: Vec<ItemType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Text to find in the id, title or notes (case-insensitive)")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Only items committed (true) or not committed (false) to the active sprint"
     )]
-    #[schemars(description = "Current backlog ordered by priority and status")]
-    pub backlog_snapshot: Vec<BacklogItem>,
-    #[serde(rename = "activeSprint", skip_serializing_if = "Option::is_none")]
-    #[schemars(description = "Active sprint plan the team is executing")]
-    pub active_sprint: Option<SprintPlan>,
-    #[serde(rename = "consensus")]
-    #[schemars(description = "Latest consensus state for moving forward")]
-    pub consensus: ConsensusState,
-    #[serde(rename = "waitingOnUser")]
-    #[schemars(description = "Whether the team is awaiting input from the user")]
-    pub waiting_on_user: bool,
+    pub in_sprint: Option<bool>,
 }
 
-#[derive(Debug, Clone)]
-pub struct TeamState {
-    pm_summaries: Vec<String>,
-    discussion_log: Vec<DiscussionPoint>,
-    backlog: HashMap<String, BacklogItem>,
-    active_sprint: Option<SprintPlan>,
-    consensus: ConsensusState,
-    awaiting_user_input: bool,
+impl BacklogFilter {
+    fn matches(&self, item: &BacklogItem, sprint_story_ids: &[String]) -> bool {
+        let has_labels = self.labels.iter().all(|wanted| {
+            item.labels
+                .iter()
+                .any(|label| label.trim().eq_ignore_ascii_case(wanted.trim()))
+        });
+        let owner = self
+            .owner
+            .as_ref()
+            .is_none_or(|owner| item.owner.as_ref() == Some(owner));
+        let status = self.status.is_empty() || self.status.contains(&item.status);
+        let priority = self.priority.is_empty() || self.priority.contains(&item.priority);
+        let item_type = self.item_type.is_empty() || self.item_type.contains(&item.item_type);
+        let text = self.text.as_deref().is_none_or(|text| {
+            let needle = text.trim().to_lowercase();
+            [Some(&item.id), Some(&item.title), item.notes.as_ref()]
+                .into_iter()
+                .flatten()
+                .any(|haystack| haystack.to_lowercase().contains(&needle))
+        });
+        let in_sprint = self
+            .in_sprint
+            .is_none_or(|wanted| sprint_story_ids.contains(&item.id) == wanted);
+
+        has_labels && owner && status && priority && item_type && text && in_sprint
+    }
 }
 
-impl Default for TeamState {
-    fn default() -> Self {
-        Self {
-            pm_summaries: Vec::new(),
-            discussion_log: Vec::new(),
-            backlog: HashMap::new(),
-            active_sprint: None,
-            consensus: ConsensusState::default(),
-            awaiting_user_input: false,
+impl fmt::Display for BacklogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn list<T: ToString>(values: &[T]) -> String {
+            values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join("/")
+        }
+
+        let mut parts = Vec::new();
+        if !self.labels.is_empty() {
+            parts.push(format!("labels {}", self.labels.join("+")));
+        }
+        if let Some(owner) = &self.owner {
+            parts.push(format!("owner {}", owner));
+        }
+        if !self.status.is_empty() {
+            parts.push(format!("status {}", list(&self.status)));
+        }
+        if !self.priority.is_empty() {
+            parts.push(format!("priority {}", list(&self.priority)));
+        }
+        if !self.item_type.is_empty() {
+            parts.push(format!("type {}", list(&self.item_type)));
+        }
+        if let Some(text) = &self.text {
+            parts.push(format!("text '{}'", text));
+        }
+        match self.in_sprint {
+            Some(true) => parts.push("in sprint".to_string()),
+            Some(false) => parts.push("not in sprint".to_string()),
+            None => {}
+        }
+
+        if parts.is_empty() {
+            write!(f, "everything")
+        } else {
+            write!(f, "{}", parts.join(", "))
         }
     }
 }
 
-impl TeamState {
-    fn process_request(&mut self, request: &DeliberateThinkingRequest) -> TeamUpdateOutcome {
-        let mut outcome = TeamUpdateOutcome::default();
+/// Backlog items matching a filter, returned by the backlog query tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogQueryResponse {
+    pub filter: String,
+    pub total: usize,
+    pub items: Vec<BacklogItem>,
+}
 
-        if !request.discussion_points.is_empty() {
-            for point in &request.discussion_points {
-                self.discussion_log.push(point.clone());
-            }
-            outcome
-                .new_discussion_points
-                .extend(request.discussion_points.clone());
+/// How `ordered_backlog` ranks items before dependencies are applied
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum PrioritizationScheme {
+    /// High, Medium and Low priority levels only
+    #[default]
+    Priority,
+    /// Reach x Impact x Confidence / Effort
+    Rice,
+    /// Weighted shortest job first: cost of delay / job size
+    Wsjf,
+}
+
+impl fmt::Display for PrioritizationScheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrioritizationScheme::Priority => write!(f, "priority level"),
+            PrioritizationScheme::Rice => write!(f, "RICE"),
+            PrioritizationScheme::Wsjf => write!(f, "WSJF"),
         }
+    }
+}
 
-        if let Some(role) = &request.role {
-            if matches!(role, TeamRole::ProjectManager) {
-                let summary = request.thought.trim();
-                if !summary.is_empty() {
-                    let summary = summary.to_string();
-                    self.pm_summaries.push(summary.clone());
-                    outcome.pm_summary = Some(summary);
-                }
-            } else if outcome.new_discussion_points.is_empty() {
-                let note = request.thought.trim();
-                if !note.is_empty() {
-                    let derived = DiscussionPoint {
-                        role: role.clone(),
-                        detail: note.to_string(),
-                    };
-                    self.discussion_log.push(derived.clone());
-                    outcome.new_discussion_points.push(derived);
-                }
+/// Inputs for RICE and WSJF scoring; effort and job size fall back to story points
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoringInputs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "RICE reach, e.g. users affected per quarter")]
+    pub reach: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "RICE impact, typically 0.25 to 3")]
+    pub impact: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "RICE confidence as a percentage from 0 to 100")]
+    pub confidence: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "RICE effort; defaults to the story points")]
+    pub effort: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "WSJF user or business value")]
+    pub business_value: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "WSJF time criticality")]
+    pub time_criticality: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "WSJF risk reduction or opportunity enablement")]
+    pub risk_reduction: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "WSJF job size; defaults to the story points")]
+    pub job_size: Option<f64>,
+}
+
+impl ScoringInputs {
+    fn fields(&self) -> [(&'static str, Option<f64>); 8] {
+        [
+            ("reach", self.reach),
+            ("impact", self.impact),
+            ("confidence", self.confidence),
+            ("effort", self.effort),
+            ("businessValue", self.business_value),
+            ("timeCriticality", self.time_criticality),
+            ("riskReduction", self.risk_reduction),
+            ("jobSize", self.job_size),
+        ]
+    }
+
+    fn validate(&self, story_id: &str) -> Result<(), McpError> {
+        for (field, value) in self.fields() {
+            if value.is_some_and(|value| !value.is_finite() || value < 0.0) {
+                return Err(create_validation_error(&format!(
+                    "{}: scoring.{} must be a non-negative number",
+                    story_id, field
+                )));
             }
         }
-
-        for story in &request.backlog_stories {
-            let change_type = if self.backlog.contains_key(&story.id) {
-                BacklogChangeType::Updated
-            } else {
-                BacklogChangeType::Added
-            };
-            self.backlog.insert(story.id.clone(), story.clone());
-            outcome
-                .backlog_changes
-                .push(BacklogChange::new(change_type, story.clone()));
+        if self.confidence.is_some_and(|confidence| confidence > 100.0) {
+            return Err(create_validation_error(&format!(
+                "{}: scoring.confidence is a percentage and cannot exceed 100",
+                story_id
+            )));
         }
-
-        for story_id in &request.remove_story_ids {
-            if let Some(removed) = self.backlog.remove(story_id) {
-                outcome
-                    .backlog_changes
-                    .push(BacklogChange::new(BacklogChangeType::Removed, removed));
+        for (field, size) in [("effort", self.effort), ("jobSize", self.job_size)] {
+            if size == Some(0.0) {
+                return Err(create_validation_error(&format!(
+                    "{}: scoring.{} must be greater than 0",
+                    story_id, field
+                )));
             }
         }
+        Ok(())
+    }
+}
 
-        if let Some(plan) = &request.sprint_plan {
-            self.active_sprint = Some(plan.clone());
-            outcome.sprint_plan_updated = Some(plan.clone());
-        }
+impl fmt::Display for ScoringInputs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts = self
+            .fields()
+            .into_iter()
+            .filter_map(|(field, value)| value.map(|value| format!("{} {}", field, value)))
+            .collect::<Vec<_>>();
+        write!(f, "{}", parts.join(", "))
+    }
+}
 
-        if let Some(update) = &request.consensus_update {
-            self.consensus.ready_for_code_changes = update.ready_for_code_changes;
-            self.consensus.blockers = update.blockers.clone();
-            self.consensus.notes = update.notes.clone();
-            outcome.consensus_state = Some(self.consensus.clone());
-        }
+/// Computed value score for a backlog item under the active scheme
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ValueScore {
+    pub story_id: String,
+    pub score: f64,
+}
 
-        if let Some(needs_input) = request.requires_user_input {
-            self.awaiting_user_input = needs_input;
-            outcome.awaiting_user_input = Some(needs_input);
-        }
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BugSeverity {
+    Critical,
+    Major,
+    Minor,
+    Trivial,
+}
 
-        outcome
+impl fmt::Display for BugSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BugSeverity::Critical => write!(f, "Critical"),
+            BugSeverity::Major => write!(f, "Major"),
+            BugSeverity::Minor => write!(f, "Minor"),
+            BugSeverity::Trivial => write!(f, "Trivial"),
+        }
     }
+}
 
-    fn generate_report(
-        &self,
-        request: &DeliberateThinkingRequest,
-        outcome: &TeamUpdateOutcome,
-    ) -> ProjectManagerReport {
-        let mut bullets = Vec::new();
-        let backlog_snapshot = self.ordered_backlog();
-        let pm_summary = outcome
-            .pm_summary
-            .clone()
-            .or_else(|| self.pm_summaries.last().cloned())
-            .or_else(|| {
-                if matches!(request.role, Some(TeamRole::ProjectManager)) {
-                    let summary = request.thought.trim();
-                    (!summary.is_empty()).then(|| summary.to_string())
-                } else {
-                    None
-                }
-            });
+/// Item counts and points for one kind of work
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeBreakdown {
+    pub item_type: ItemType,
+    pub total_items: u32,
+    pub done_items: u32,
+    pub total_points: u32,
+}
 
-        let pm_summary_text = pm_summary
-            .clone()
-            .unwrap_or_else(|| "No project manager summary provided yet".to_string());
-        bullets.push(format!("PM summary: {}", pm_summary_text));
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AcceptanceCriterion {
+    #[schemars(description = "Condition the story must satisfy")]
+    pub description: String,
+    #[serde(default)]
+    #[schemars(description = "Whether the condition has been verified")]
+    pub checked: bool,
+}
 
-        if !outcome.new_discussion_points.is_empty() {
-            let discussion = outcome
-                .new_discussion_points
-                .iter()
-                .map(|point| format!("{}: {}", point.role, point.detail))
-                .collect::<Vec<_>>()
-                .join("; ");
-            bullets.push(format!("Discussion points: {}", discussion));
-        } else if let Some(last_point) = self.discussion_log.last() {
-            bullets.push(format!(
-                "Discussion points: {}: {}",
-                last_point.role, last_point.detail
-            ));
-        } else {
-            bullets.push("Discussion points: none recorded yet".to_string());
+impl fmt::Display for AcceptanceCriterion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mark = if self.checked { "x" } else { " " };
+        write!(f, "[{}] {}", mark, self.description)
+    }
+}
+
+/// Partial update for an existing backlog story
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogPatch {
+    #[schemars(description = "Identifier of the story to update")]
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New title")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New priority")]
+    pub priority: Option<PriorityLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New delivery status")]
+    pub status: Option<StoryStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Why the status is changing")]
+    pub status_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New accountable role")]
+    pub owner: Option<TeamRole>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New implementation notes")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Replacement list of dependency identifiers")]
+    pub depends_on: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New story point estimate")]
+    pub story_points: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Replacement RICE/WSJF scoring inputs")]
+    pub scoring: Option<ScoringInputs>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Replacement acceptance criteria")]
+    pub acceptance_criteria: Option<Vec<AcceptanceCriterion>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Descriptions of acceptance criteria to check off")]
+    pub check_criteria: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Replacement list of confirmed definition-of-done items")]
+    pub done_checklist: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New epic or parent item")]
+    pub parent_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New item type")]
+    pub item_type: Option<ItemType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New bug severity")]
+    pub severity: Option<BugSeverity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Replacement reproduction steps")]
+    pub reproduction_steps: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New spike timebox in thoughts")]
+    pub timebox_thoughts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New expected spike outcome")]
+    pub expected_outcome: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Replacement labels")]
+    pub labels: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Optional fields to remove from the story")]
+    pub clear_fields: Vec<ClearableField>,
+}
+
+/// Optional backlog fields a patch can remove
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ClearableField {
+    Owner,
+    Notes,
+    StoryPoints,
+    Scoring,
+    ParentId,
+    Severity,
+    ReproductionSteps,
+    TimeboxThoughts,
+    ExpectedOutcome,
+}
+
+impl ClearableField {
+    fn name(&self) -> &'static str {
+        match self {
+            ClearableField::Owner => "owner",
+            ClearableField::Notes => "notes",
+            ClearableField::StoryPoints => "storyPoints",
+            ClearableField::Scoring => "scoring",
+            ClearableField::ParentId => "parentId",
+            ClearableField::Severity => "severity",
+            ClearableField::ReproductionSteps => "reproductionSteps",
+            ClearableField::TimeboxThoughts => "timeboxThoughts",
+            ClearableField::ExpectedOutcome => "expectedOutcome",
         }
+    }
 
-        if !outcome.backlog_changes.is_empty() {
-            let changes = outcome
-                .backlog_changes
-                .iter()
-                .map(|change| change.summary())
-                .collect::<Vec<_>>()
-                .join("; ");
-            bullets.push(format!("Backlog updates: {}", changes));
-        } else if backlog_snapshot.is_empty() {
-            bullets.push("Backlog updates: backlog is empty".to_string());
-        } else {
-            let highlights = backlog_snapshot
-                .iter()
-                .take(3)
-                .map(|item| format!("{} [{} | {}]", item.id, item.priority, item.status))
-                .collect::<Vec<_>>()
-                .join("; ");
-            bullets.push(format!("Backlog focus: {}", highlights));
+    /// Whether the patch also supplies a new value for this field
+    fn is_set_by(&self, patch: &BacklogPatch) -> bool {
+        match self {
+            ClearableField::Owner => patch.owner.is_some(),
+            ClearableField::Notes => patch.notes.is_some(),
+            ClearableField::StoryPoints => patch.story_points.is_some(),
+            ClearableField::Scoring => patch.scoring.is_some(),
+            ClearableField::ParentId => patch.parent_id.is_some(),
+            ClearableField::Severity => patch.severity.is_some(),
+            ClearableField::ReproductionSteps => patch.reproduction_steps.is_some(),
+            ClearableField::TimeboxThoughts => patch.timebox_thoughts.is_some(),
+            ClearableField::ExpectedOutcome => patch.expected_outcome.is_some(),
         }
+    }
 
-        if let Some(plan) = outcome
-            .sprint_plan_updated
-            .as_ref()
-            .or_else(|| self.active_sprint.as_ref())
-        {
-            let stories = if plan.committed_story_ids.is_empty() {
-                "no stories committed".to_string()
-            } else {
-                plan.committed_story_ids.join(", ")
+    fn clear(&self, item: &mut BacklogItem) {
+        match self {
+            ClearableField::Owner => item.owner = None,
+            ClearableField::Notes => item.notes = None,
+            ClearableField::StoryPoints => item.story_points = None,
+            ClearableField::Scoring => item.scoring = None,
+            ClearableField::ParentId => item.parent_id = None,
+            ClearableField::Severity => item.severity = None,
+            ClearableField::ReproductionSteps => item.reproduction_steps.clear(),
+            ClearableField::TimeboxThoughts => item.timebox_thoughts = None,
+            ClearableField::ExpectedOutcome => item.expected_outcome = None,
+        }
+    }
+
+    /// Type-specific fields that only apply to `item_type`
+    fn type_specific(item_type: ItemType) -> &'static [ClearableField] {
+        match item_type {
+            ItemType::Bug => &[ClearableField::Severity, ClearableField::ReproductionSteps],
+            ItemType::Spike => &[
+                ClearableField::TimeboxThoughts,
+                ClearableField::ExpectedOutcome,
+            ],
+            _ => &[],
+        }
+    }
+}
+
+impl BacklogPatch {
+    /// Returns the story with the supplied fields replaced and `clearFields` removed.
+    /// Changing the item type drops the old type's fields unless the patch sets them.
+    fn apply(&self, item: &BacklogItem) -> Result<BacklogItem, McpError> {
+        let mut patched = item.clone();
+        for field in &self.clear_fields {
+            if field.is_set_by(self) {
+                return Err(create_validation_error(&format!(
+                    "backlogPatches.clearFields: {} cannot both set and clear {}",
+                    item.id,
+                    field.name()
+                )));
+            }
+            field.clear(&mut patched);
+        }
+        if let Some(title) = &self.title {
+            patched.title = title.clone();
+        }
+        if let Some(priority) = &self.priority {
+            patched.priority = priority.clone();
+        }
+        if let Some(status) = &self.status {
+            if status != &patched.status {
+                // A stale reason must not justify a new transition
+                patched.status_reason = None;
+            }
+            patched.status = status.clone();
+        }
+        if let Some(reason) = &self.status_reason {
+            patched.status_reason = Some(reason.clone());
+        }
+        if let Some(owner) = &self.owner {
+            patched.owner = Some(owner.clone());
+        }
+        if let Some(notes) = &self.notes {
+            patched.notes = Some(notes.clone());
+        }
+        if let Some(depends_on) = &self.depends_on {
+            patched.depends_on = depends_on.clone();
+        }
+        if let Some(points) = self.story_points {
+            patched.story_points = Some(points);
+        }
+        if let Some(criteria) = &self.acceptance_criteria {
+            patched.acceptance_criteria = criteria.clone();
+        }
+        for description in &self.check_criteria {
+            let criterion = patched
+                .acceptance_criteria
+                .iter_mut()
+                .find(|criterion| &criterion.description == description)
+                .ok_or_else(|| {
+                    create_validation_error(&format!(
+                        "backlogPatches.checkCriteria: {} has no acceptance criterion '{}'",
+                        item.id, description
+                    ))
+                })?;
+            criterion.checked = true;
+        }
+        if let Some(checklist) = &self.done_checklist {
+            patched.done_checklist = checklist.clone();
+        }
+        if let Some(parent_id) = &self.parent_id {
+            patched.parent_id = Some(parent_id.clone());
+        }
+        if let Some(item_type) = self.item_type {
+            if item_type != item.item_type {
+                for field in ClearableField::type_specific(item.item_type) {
+                    if !field.is_set_by(self) {
+                        field.clear(&mut patched);
+                    }
+                }
+            }
+            patched.item_type = item_type;
+        }
+        if let Some(severity) = self.severity {
+            patched.severity = Some(severity);
+        }
+        if let Some(steps) = &self.reproduction_steps {
+            patched.reproduction_steps = steps.clone();
+        }
+        if let Some(timebox) = self.timebox_thoughts {
+            patched.timebox_thoughts = Some(timebox);
+        }
+        if let Some(outcome) = &self.expected_outcome {
+            patched.expected_outcome = Some(outcome.clone());
+        }
+        if let Some(labels) = &self.labels {
+            patched.labels = labels.clone();
+        }
+        if let Some(scoring) = &self.scoring {
+            patched.scoring = Some(scoring.clone());
+        }
+        Ok(patched)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintParticipant {
+    #[schemars(description = "Role assigned to this sprint")]
+    pub role: TeamRole,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Reason this role is included in the sprint")]
+    pub reasoning: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Key responsibilities for this role")]
+    pub responsibilities: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Story points this role can take on during the sprint")]
+    pub capacity_points: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintPlan {
+    #[schemars(description = "Sprint name or identifier")]
+    pub sprint_name: String,
+    #[schemars(description = "Goal for the sprint")]
+    pub goal: String,
+    #[schemars(description = "Planned duration of the sprint in days", range(min = 1))]
+    pub duration_days: u32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Participants for the sprint with rationale")]
+    pub participants: Vec<SprintParticipant>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Backlog stories committed to this sprint")]
+    pub committed_story_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(
+        description = "Known risks; each new one is added to the risk register with medium likelihood and impact"
+    )]
+    pub risks: Vec<String>,
+    /// Open retrospective actions carried into this sprint; set by the server
+    #[serde(default, skip_serializing_if = "Vec::is_empty", skip_deserializing)]
+    #[schemars(skip)]
+    pub retro_action_ids: Vec<String>,
+}
+
+/// Planned versus completed story points for a sprint
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintPoints {
+    #[schemars(description = "Sprint these points belong to")]
+    pub sprint_name: String,
+    #[schemars(description = "Story points committed to the sprint")]
+    pub planned_points: u32,
+    #[schemars(description = "Story points of committed stories that are Done")]
+    pub completed_points: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Combined capacity of participants that declared one")]
+    pub capacity_points: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Committed stories without a story point estimate")]
+    pub unestimated_story_ids: Vec<String>,
+}
+
+/// Options for closing the active sprint
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintClosure {
+    #[serde(default = "default_carry_over")]
+    #[schemars(description = "Whether incomplete commitments carry over into the next sprint")]
+    pub carry_over: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Closing notes from the project manager")]
+    pub notes: Option<String>,
+}
+
+fn default_carry_over() -> bool {
+    true
+}
+
+impl Default for SprintClosure {
+    fn default() -> Self {
+        Self {
+            carry_over: default_carry_over(),
+            notes: None,
+        }
+    }
+}
+
+/// The sprint currently being executed
+#[derive(Debug, Clone)]
+struct ActiveSprint {
+    plan: SprintPlan,
+    started_at_thought: u32,
+    started_at: DateTime<Utc>,
+    burndown: Vec<BurndownPoint>,
+}
+
+impl ActiveSprint {
+    /// Records remaining work, replacing an earlier sample from the same thought
+    fn record_burndown(&mut self, point: BurndownPoint) {
+        match self.burndown.last_mut() {
+            Some(last) if last.thought_number == point.thought_number => *last = point,
+            _ => self.burndown.push(point),
+        }
+    }
+}
+
+/// Remaining sprint work sampled at one thought
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BurndownPoint {
+    pub thought_number: u32,
+    pub recorded_at: DateTime<Utc>,
+    #[schemars(description = "Committed story points not yet Done")]
+    pub remaining_points: u32,
+    #[schemars(description = "Committed story points already Done")]
+    pub completed_points: u32,
+}
+
+/// Remaining sprint work at the end of a calendar day
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyBurndown {
+    pub date: NaiveDate,
+    pub remaining_points: u32,
+    pub completed_points: u32,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum BurndownGranularity {
+    #[default]
+    Thought,
+    Day,
+}
+
+/// Burndown series for the active sprint
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BurndownReport {
+    pub sprint_name: String,
+    pub planned_points: u32,
+    #[schemars(description = "Remaining points after each thought")]
+    pub by_thought: Vec<BurndownPoint>,
+    #[schemars(description = "Remaining points at the end of each day")]
+    pub by_day: Vec<DailyBurndown>,
+}
+
+impl BurndownReport {
+    fn new(sprint_name: &str, planned_points: u32, points: &[BurndownPoint]) -> Self {
+        let mut by_day: Vec<DailyBurndown> = Vec::new();
+        for point in points {
+            let daily = DailyBurndown {
+                date: point.recorded_at.date_naive(),
+                remaining_points: point.remaining_points,
+                completed_points: point.completed_points,
             };
-            let participants = if plan.participants.is_empty() {
-                "no participants selected".to_string()
+            match by_day.last_mut() {
+                Some(last) if last.date == daily.date => *last = daily,
+                _ => by_day.push(daily),
+            }
+        }
+
+        Self {
+            sprint_name: sprint_name.to_string(),
+            planned_points,
+            by_thought: points.to_vec(),
+            by_day,
+        }
+    }
+
+    /// Renders the series as horizontal bars, one row per thought or day
+    fn ascii_chart(&self, granularity: BurndownGranularity) -> String {
+        let rows: Vec<(String, u32)> = match granularity {
+            BurndownGranularity::Thought => self
+                .by_thought
+                .iter()
+                .map(|point| (format!("T{}", point.thought_number), point.remaining_points))
+                .collect(),
+            BurndownGranularity::Day => self
+                .by_day
+                .iter()
+                .map(|day| (day.date.format("%m-%d").to_string(), day.remaining_points))
+                .collect(),
+        };
+        let unit = match granularity {
+            BurndownGranularity::Thought => "thought",
+            BurndownGranularity::Day => "day",
+        };
+        format!(
+            "Burndown {} (remaining of {} points per {}):\n{}",
+            self.sprint_name,
+            self.planned_points,
+            unit,
+            ascii_bars(&rows, self.planned_points)
+        )
+    }
+}
+
+/// Completed points for one closed sprint in the velocity window
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintVelocity {
+    pub sprint_name: String,
+    pub completed_points: u32,
+}
+
+/// Rolling velocity across the most recently closed sprints
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VelocityReport {
+    #[schemars(description = "Number of closed sprints the average is taken over")]
+    pub window: usize,
+    pub sprints: Vec<SprintVelocity>,
+    pub average_points: f64,
+}
+
+impl VelocityReport {
+    fn ascii_chart(&self) -> String {
+        let rows: Vec<(String, u32)> = self
+            .sprints
+            .iter()
+            .map(|sprint| (sprint.sprint_name.clone(), sprint.completed_points))
+            .collect();
+        let max = rows.iter().map(|(_, value)| *value).max().unwrap_or(0);
+        format!(
+            "Velocity (rolling {} sprint(s), average {:.1} points):\n{}",
+            self.sprints.len(),
+            self.average_points,
+            ascii_bars(&rows, max)
+        )
+    }
+}
+
+/// Draws labelled horizontal bars scaled so `max` fills the chart width
+fn ascii_bars(rows: &[(String, u32)], max: u32) -> String {
+    const WIDTH: u64 = 20;
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    rows.iter()
+        .map(|(label, value)| {
+            let filled = if max == 0 {
+                0
             } else {
-                plan.participants
-                    .iter()
-                    .map(|participant| {
-                        let mut detail = participant.role.to_string();
-                        if let Some(reason) = &participant.reasoning {
-                            if !reason.trim().is_empty() {
-                                detail.push_str(&format!(" ({})", reason));
-                            }
-                        }
-                        if !participant.responsibilities.is_empty() {
-                            detail.push_str(&format!(
-                                " - {}",
-                                participant.responsibilities.join(", ")
-                            ));
-                        }
-                        detail
-                    })
-                    .collect::<Vec<_>>()
-                    .join("; ")
+                (u64::from(*value) * WIDTH)
+                    .div_ceil(u64::from(max))
+                    .min(WIDTH)
             };
-            bullets.push(format!(
-                "Sprint plan: {} goal '{}' lasting {} day(s); stories {}; participants {}",
-                plan.sprint_name, plan.goal, plan.duration_days, stories, participants
-            ));
-        } else {
-            bullets.push("Sprint plan: not yet defined".to_string());
+            format!(
+                "{:>width$} | {}{} {}",
+                label,
+                "#".repeat(filled as usize),
+                " ".repeat((WIDTH - filled) as usize),
+                value,
+                width = label_width
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// An archived sprint with its outcome
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintRecord {
+    pub plan: SprintPlan,
+    pub started_at_thought: u32,
+    pub closed_at_thought: u32,
+    pub started_at: DateTime<Utc>,
+    pub closed_at: DateTime<Utc>,
+    #[schemars(description = "Committed stories that were Done at close")]
+    pub completed_story_ids: Vec<String>,
+    #[schemars(description = "Committed stories that were not Done at close")]
+    pub incomplete_story_ids: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Incomplete stories offered to the next sprint")]
+    pub carried_over_story_ids: Vec<String>,
+    pub points: SprintPoints,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Remaining points after each thought of the sprint")]
+    pub burndown: Vec<BurndownPoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Retrospective entries, one per role")]
+    pub retrospective: Vec<RetroEntry>,
+}
+
+/// One role's retrospective for a closed sprint
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetroEntry {
+    #[schemars(description = "Role giving this retrospective")]
+    pub role: TeamRole,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "What went well during the sprint")]
+    pub went_well: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "What the team should improve")]
+    pub to_improve: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Follow-up actions agreed in the retrospective")]
+    pub action_items: Vec<RetroActionItem>,
+}
+
+/// Follow-up proposed in a retrospective entry
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetroActionItem {
+    #[schemars(description = "What needs to happen")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Role responsible for the action")]
+    pub owner: Option<TeamRole>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Create a backlog story with this id instead of a tracked retro task"
+    )]
+    pub story_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum RetroActionStatus {
+    Open,
+    Done,
+}
+
+/// Retrospective action, tracked on its own or through a backlog story
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetroAction {
+    pub id: String,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<TeamRole>,
+    #[schemars(description = "Sprint whose retrospective raised the action")]
+    pub sprint_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Backlog story tracking the action; it closes when the story is Done"
+    )]
+    pub story_id: Option<String>,
+    pub status: RetroActionStatus,
+}
+
+impl RetroAction {
+    /// Whether an action item from `sprint_name`'s retrospective is this action
+    fn matches(&self, sprint_name: &str, item: &RetroActionItem) -> bool {
+        self.sprint_name == sprint_name
+            && match (&self.story_id, &item.story_id) {
+                (Some(existing), Some(story_id)) => existing == story_id,
+                (None, None) => self
+                    .description
+                    .eq_ignore_ascii_case(item.description.trim()),
+                _ => false,
+            }
+    }
+}
+
+impl fmt::Display for RetroAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.id, self.description)?;
+        if let Some(owner) = &self.owner {
+            write!(f, " ({})", owner)?;
+        }
+        if let Some(story_id) = &self.story_id {
+            write!(f, " [story {}]", story_id)?;
+        }
+        Ok(())
+    }
+}
+
+/// A decision as submitted with a thought
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DecisionInput {
+    #[schemars(description = "Short name of the decision, e.g. 'Store sessions in Redis'")]
+    pub title: String,
+    #[schemars(description = "Forces and constraints that made a decision necessary")]
+    pub context: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Options that were considered")]
+    pub options: Vec<String>,
+    #[schemars(description = "The option the team chose")]
+    pub chosen_option: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Expected consequences, good and bad")]
+    pub consequences: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Roles that made the decision; defaults to the submitting role")]
+    pub deciding_roles: Vec<TeamRole>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Id of an accepted decision this one replaces, e.g. ADR-0002")]
+    pub supersedes: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum DecisionStatus {
+    Accepted,
+    Superseded,
+}
+
+impl fmt::Display for DecisionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecisionStatus::Accepted => write!(f, "Accepted"),
+            DecisionStatus::Superseded => write!(f, "Superseded"),
+        }
+    }
+}
+
+/// Architecture decision record kept in the decision log
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DecisionRecord {
+    pub id: String,
+    pub title: String,
+    pub context: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    pub chosen_option: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub consequences: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deciding_roles: Vec<TeamRole>,
+    #[schemars(description = "Thought the decision came from")]
+    pub thought_number: u32,
+    pub decided_at: DateTime<Utc>,
+    pub status: DecisionStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub supersedes: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub superseded_by: Option<String>,
+}
+
+impl DecisionRecord {
+    /// Position in the decision log, starting at 1
+    fn number(&self) -> u32 {
+        self.id
+            .trim_start_matches("ADR-")
+            .parse()
+            .unwrap_or_default()
+    }
+
+    /// Numbered ADR file name, e.g. `0003-store-sessions-in-redis.md`; titles with no
+    /// letters or digits fall back to `decision`
+    fn file_name(&self) -> String {
+        let mut slug = String::new();
+        for c in self.title.chars() {
+            if c.is_alphanumeric() {
+                slug.extend(c.to_lowercase());
+            } else if !slug.is_empty() && !slug.ends_with('-') {
+                slug.push('-');
+            }
+        }
+        let slug = match slug.trim_end_matches('-') {
+            "" => "decision",
+            slug => slug,
+        };
+        format!("{:04}-{}.md", self.number(), slug)
+    }
+
+    /// Renders the record in the usual ADR layout
+    fn to_markdown(&self, log: &[DecisionRecord]) -> String {
+        let link = |id: &str| {
+            log.iter()
+                .find(|record| record.id == id)
+                .map(|record| {
+                    format!(
+                        "[{}. {}]({})",
+                        record.number(),
+                        record.title,
+                        record.file_name()
+                    )
+                })
+                .unwrap_or_else(|| id.to_string())
+        };
+
+        let mut status = vec![self.status.to_string()];
+        if let Some(id) = &self.superseded_by {
+            status = vec![format!("Superseded by {}", link(id))];
+        }
+        if let Some(id) = &self.supersedes {
+            status.push(format!("Supersedes {}", link(id)));
+        }
+
+        let options = if self.options.is_empty() {
+            format!("- {}", self.chosen_option)
+        } else {
+            self.options
+                .iter()
+                .map(|option| {
+                    if option == &self.chosen_option {
+                        format!("- {} (chosen)", option)
+                    } else {
+                        format!("- {}", option)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        let origin = if self.deciding_roles.is_empty() {
+            format!("Recorded in thought {}.", self.thought_number)
+        } else {
+            let roles = self
+                .deciding_roles
+                .iter()
+                .map(|role| role.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("Decided by {} in thought {}.", roles, self.thought_number)
+        };
+
+        let consequences = if self.consequences.is_empty() {
+            "None recorded.".to_string()
+        } else {
+            self.consequences
+                .iter()
+                .map(|consequence| format!("- {}", consequence))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+
+        format!(
+            "# {}. {}\n\nDate: {}\n\n## Status\n\n{}\n\n## Context\n\n{}\n\n## Options Considered\n\n{}\n\n## Decision\n\nWe chose {}. {}\n\n## Consequences\n\n{}\n",
+            self.number(),
+            self.title,
+            self.decided_at.format("%Y-%m-%d"),
+            status.join("\n\n"),
+            self.context,
+            options,
+            self.chosen_option,
+            origin,
+            consequences
+        )
+    }
+}
+
+/// Decision log export format
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum DecisionLogFormat {
+    /// Decision records as JSON
+    #[default]
+    Records,
+    /// One numbered ADR Markdown file per decision
+    AdrMarkdown,
+}
+
+/// Parameters for the decision log tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DecisionLogRequest {
+    #[serde(default)]
+    #[schemars(description = "records (default) or adrMarkdown")]
+    pub format: DecisionLogFormat,
+    #[serde(default)]
+    #[schemars(description = "Leave out decisions that have been superseded")]
+    pub accepted_only: bool,
+}
+
+/// A generated ADR Markdown file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdrFile {
+    pub file_name: String,
+    pub content: String,
+}
+
+/// Decision log contents returned by the decision log tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecisionLogResponse {
+    pub decisions: Vec<DecisionRecord>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<AdrFile>,
+}
+
+/// Likelihood or impact rating for a risk
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum RiskLevel {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    fn score(&self) -> u8 {
+        match self {
+            RiskLevel::Low => 1,
+            RiskLevel::Medium => 2,
+            RiskLevel::High => 3,
+        }
+    }
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskLevel::Low => write!(f, "Low"),
+            RiskLevel::Medium => write!(f, "Medium"),
+            RiskLevel::High => write!(f, "High"),
+        }
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum RiskStatus {
+    #[default]
+    Open,
+    Mitigating,
+    /// Left in place on purpose; no further mitigation planned
+    Accepted,
+    Closed,
+}
+
+impl fmt::Display for RiskStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskStatus::Open => write!(f, "Open"),
+            RiskStatus::Mitigating => write!(f, "Mitigating"),
+            RiskStatus::Accepted => write!(f, "Accepted"),
+            RiskStatus::Closed => write!(f, "Closed"),
+        }
+    }
+}
+
+/// Adds a risk (no id) or changes the supplied fields of a registered risk
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Id of the registered risk to update; omit to add a new risk")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "What could go wrong (required for new risks)")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "How likely the risk is: low, medium or high")]
+    pub likelihood: Option<RiskLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "How bad it would be: low, medium or high")]
+    pub impact: Option<RiskLevel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Plan for reducing the risk")]
+    pub mitigation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Role responsible for the risk")]
+    pub owner: Option<TeamRole>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<RiskStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Backlog stories the risk affects (replaces the current links)")]
+    pub story_ids: Option<Vec<String>>,
+}
+
+/// Entry in the persistent risk register
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Risk {
+    pub id: String,
+    pub description: String,
+    pub likelihood: RiskLevel,
+    pub impact: RiskLevel,
+    #[schemars(description = "Likelihood score times impact score, from 1 to 9")]
+    pub exposure: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mitigation: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<TeamRole>,
+    pub status: RiskStatus,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub story_ids: Vec<String>,
+    #[schemars(description = "Thought in which the risk was raised")]
+    pub raised_at_thought: u32,
+}
+
+impl Risk {
+    fn is_active(&self) -> bool {
+        matches!(self.status, RiskStatus::Open | RiskStatus::Mitigating)
+    }
+
+    fn apply(&mut self, update: &RiskUpdate) {
+        if let Some(description) = &update.description {
+            self.description = description.clone();
+        }
+        if let Some(likelihood) = update.likelihood {
+            self.likelihood = likelihood;
+        }
+        if let Some(impact) = update.impact {
+            self.impact = impact;
+        }
+        if let Some(mitigation) = &update.mitigation {
+            self.mitigation = Some(mitigation.clone());
+        }
+        if let Some(owner) = &update.owner {
+            self.owner = Some(owner.clone());
+        }
+        if let Some(status) = update.status {
+            self.status = status;
+        }
+        if let Some(story_ids) = &update.story_ids {
+            self.story_ids = story_ids.clone();
+        }
+        self.exposure = self.likelihood.score() * self.impact.score();
+    }
+}
+
+impl fmt::Display for Risk {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} (exposure {}: {} likelihood, {} impact",
+            self.id, self.description, self.exposure, self.likelihood, self.impact
+        )?;
+        if let Some(owner) = &self.owner {
+            write!(f, ", owner {}", owner)?;
+        }
+        if !self.story_ids.is_empty() {
+            write!(f, ", affects {}", self.story_ids.join(", "))?;
+        }
+        write!(f, ")")
+    }
+}
+
+/// Parameters for the risk register tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskRegisterRequest {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Only return risks in these statuses")]
+    pub status: Vec<RiskStatus>,
+}
+
+/// Risk register contents, highest exposure first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskRegisterResponse {
+    pub risks: Vec<Risk>,
+}
+
+impl SprintRecord {
+    fn summary(&self) -> String {
+        let total = self.completed_story_ids.len() + self.incomplete_story_ids.len();
+        let mut summary = format!(
+            "{} completed {}/{} stories ({}/{} points)",
+            self.plan.sprint_name,
+            self.completed_story_ids.len(),
+            total,
+            self.points.completed_points,
+            self.points.planned_points
+        );
+        if !self.incomplete_story_ids.is_empty() {
+            summary.push_str(&format!(
+                "; incomplete {}",
+                self.incomplete_story_ids.join(", ")
+            ));
+        }
+        if !self.carried_over_story_ids.is_empty() {
+            summary.push_str("; carried over to the next sprint");
+        }
+        summary
+    }
+}
+
+/// A question the team wants the user to answer
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionInput {
+    #[schemars(description = "The question for the user")]
+    pub question: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Suggested answers; the user may still answer freely")]
+    pub options: Vec<String>,
+    #[serde(default = "default_blocking")]
+    #[schemars(description = "Whether the team must wait for the answer (default true)")]
+    pub blocking: bool,
+}
+
+fn default_blocking() -> bool {
+    true
+}
+
+/// Elicitation field holding the answer to the thought itself
+const THOUGHT_ANSWER_KEY: &str = "thought";
+
+/// Entry in the queue of questions for the user
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UserQuestion {
+    pub id: String,
+    pub question: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Role that asked the question")]
+    pub asked_by: Option<TeamRole>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+    pub blocking: bool,
+    pub asked_at_thought: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub answered_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Thought whose requiresUserInput: false withdrew the question")]
+    pub dismissed_at_thought: Option<u32>,
+}
+
+impl fmt::Display for UserQuestion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.id)?;
+        if self.blocking {
+            write!(f, "(blocking) ")?;
+        }
+        write!(f, "{}", self.question)?;
+        if !self.options.is_empty() {
+            write!(f, " [{}]", self.options.join(" / "))?;
+        }
+        if let Some(role) = &self.asked_by {
+            write!(f, " asked by {}", role)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parameters for the answer question tool
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnswerQuestionRequest {
+    #[schemars(description = "Id of the question being answered, e.g. Q-1")]
+    pub question_id: String,
+    #[schemars(description = "The user's answer")]
+    pub answer: String,
+}
+
+/// Answered question and what the team is still waiting on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnswerQuestionResponse {
+    pub question: UserQuestion,
+    pub waiting_on_user: bool,
+    pub open_questions: Vec<UserQuestion>,
+}
+
+/// Parameters for the sprint history tool
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct SprintHistoryRequest {
+    #[serde(rename = "sprintName", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Only return the sprint with this name")]
+    pub sprint_name: Option<String>,
+}
+
+/// Closed sprints returned by the sprint history tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SprintHistoryResponse {
+    pub sprints: Vec<SprintRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusUpdate {
+    #[schemars(description = "Whether the submitting role votes that code changes are ready")]
+    pub ready_for_code_changes: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Why the submitting role is or is not ready")]
+    pub rationale: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(
+        description = "Blockers to raise; descriptions matching an open blocker are ignored, and blockers are resolved through the top-level blockers field"
+    )]
+    pub blockers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Additional notes from the project manager")]
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusVote {
+    #[schemars(description = "Role that cast the vote")]
+    pub role: TeamRole,
+    #[schemars(description = "Whether the role is ready for code changes")]
+    pub ready: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Reasoning behind the vote")]
+    pub rationale: Option<String>,
+    #[schemars(description = "Thought number the vote was cast in")]
+    pub thought_number: u32,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum QuorumMode {
+    #[default]
+    Unanimous,
+    Majority,
+}
+
+impl fmt::Display for QuorumMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuorumMode::Unanimous => write!(f, "unanimous"),
+            QuorumMode::Majority => write!(f, "majority"),
+        }
+    }
+}
+
+/// Rule deciding when the team's votes add up to overall readiness
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QuorumRule {
+    #[schemars(description = "How many roles must vote ready")]
+    pub mode: QuorumMode,
+    #[schemars(description = "Whether the project manager must vote ready")]
+    pub require_pm_sign_off: bool,
+}
+
+impl QuorumRule {
+    /// Returns overall readiness and the roles holding it up
+    fn evaluate(&self, votes: &[ConsensusVote]) -> (bool, Vec<TeamRole>) {
+        let is_ready = |role: &TeamRole| votes.iter().any(|vote| &vote.role == role && vote.ready);
+        let roles = TeamRole::all();
+        let holding_up: Vec<TeamRole> = roles
+            .iter()
+            .filter(|role| !is_ready(role))
+            .cloned()
+            .collect();
+
+        let quorum_met = match self.mode {
+            QuorumMode::Unanimous => holding_up.is_empty(),
+            QuorumMode::Majority => (roles.len() - holding_up.len()) * 2 > roles.len(),
+        };
+        let sign_off_met = !self.require_pm_sign_off || is_ready(&TeamRole::ProjectManager);
+
+        (quorum_met && sign_off_met, holding_up)
+    }
+}
+
+impl fmt::Display for QuorumRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.mode)?;
+        if self.require_pm_sign_off {
+            write!(f, " with PM sign-off")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum BlockerStatus {
+    #[default]
+    Open,
+    Resolved,
+}
+
+/// Raises a blocker (no id) or changes an existing one
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockerUpdate {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Id of the blocker to change; omit to raise a new blocker")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "What is blocking the team (required for new blockers)")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Role responsible for clearing the blocker")]
+    pub owner: Option<TeamRole>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Backlog stories held up by the blocker (replaces the current links)"
+    )]
+    pub story_ids: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "resolved to resolve the blocker, open to reopen it")]
+    pub status: Option<BlockerStatus>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "How the blocker was resolved")]
+    pub resolution: Option<String>,
+}
+
+/// Something stopping the team, tracked from when it was raised until it is resolved
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Blocker {
+    pub id: String,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<TeamRole>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub story_ids: Vec<String>,
+    pub status: BlockerStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Role that raised the blocker")]
+    pub raised_by: Option<TeamRole>,
+    #[schemars(description = "Thought in which the blocker was first raised")]
+    pub raised_at_thought: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Thought in which the blocker was last reopened")]
+    pub reopened_at_thought: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_at_thought: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<String>,
+}
+
+impl Blocker {
+    /// Thought from which the current open period is measured
+    fn opened_at_thought(&self) -> u32 {
+        self.reopened_at_thought.unwrap_or(self.raised_at_thought)
+    }
+
+    /// Thoughts between the blocker (re)opening and its resolution
+    fn resolution_thoughts(&self) -> Option<u32> {
+        self.resolved_at_thought
+            .map(|resolved| resolved.saturating_sub(self.opened_at_thought()))
+    }
+
+    fn apply(&mut self, thought_number: u32, update: &BlockerUpdate) {
+        if let Some(description) = &update.description {
+            self.description = description.clone();
+        }
+        if let Some(owner) = &update.owner {
+            self.owner = Some(owner.clone());
+        }
+        if let Some(story_ids) = &update.story_ids {
+            self.story_ids = story_ids.clone();
+        }
+        if let Some(resolution) = &update.resolution {
+            self.resolution = Some(resolution.clone());
+        }
+        match update.status {
+            Some(BlockerStatus::Resolved) if self.status == BlockerStatus::Open => {
+                self.status = BlockerStatus::Resolved;
+                self.resolved_at_thought = Some(thought_number);
+            }
+            Some(BlockerStatus::Open) if self.status == BlockerStatus::Resolved => {
+                self.status = BlockerStatus::Open;
+                self.reopened_at_thought = Some(thought_number);
+                self.resolved_at_thought = None;
+                if update.resolution.is_none() {
+                    self.resolution = None;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Blocker {
+    /// Whether raising `description` would duplicate this blocker
+    fn is_open_duplicate(&self, description: &str) -> bool {
+        self.status == BlockerStatus::Open && self.description.eq_ignore_ascii_case(description)
+    }
+
+    /// Folds a duplicate raise into this blocker: story ids are added and the owner is
+    /// set when there is none. Returns a warning when the owners disagree.
+    fn merge(&mut self, update: &BlockerUpdate) -> Option<String> {
+        for story_id in update.story_ids.iter().flatten() {
+            if !self.story_ids.contains(story_id) {
+                self.story_ids.push(story_id.clone());
+            }
+        }
+        match (&self.owner, &update.owner) {
+            (None, Some(owner)) => {
+                self.owner = Some(owner.clone());
+                None
+            }
+            (Some(current), Some(owner)) if current != owner => Some(format!(
+                "{} is already owned by {}; ignored owner {} from the duplicate",
+                self.id, current, owner
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Blocker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.id, self.description)?;
+        let mut details = Vec::new();
+        if let Some(owner) = &self.owner {
+            details.push(format!("owner {}", owner));
+        }
+        if !self.story_ids.is_empty() {
+            details.push(format!("blocks {}", self.story_ids.join(", ")));
+        }
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// How quickly blockers are being cleared, measured in thoughts
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockerResolutionReport {
+    pub open: usize,
+    pub resolved: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Mean thoughts from raising (or reopening) to resolution")]
+    pub average_resolution_thoughts: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longest_resolution_thoughts: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Open blocker that has been open the longest")]
+    pub oldest_open: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub oldest_open_thoughts: Option<u32>,
+}
+
+impl fmt::Display for BlockerResolutionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} open", self.open)?;
+        if let (Some(oldest), Some(age)) = (&self.oldest_open, self.oldest_open_thoughts) {
+            write!(f, " (oldest {} open for {} thought(s))", oldest, age)?;
+        }
+        write!(f, ", {} resolved", self.resolved)?;
+        if let (Some(average), Some(longest)) = (
+            self.average_resolution_thoughts,
+            self.longest_resolution_thoughts,
+        ) {
+            write!(
+                f,
+                " in {:.1} thought(s) on average (longest {})",
+                average, longest
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Parameters for the blocker log tool
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockerLogRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Only return blockers with this status")]
+    pub status: Option<BlockerStatus>,
+}
+
+/// Blockers with their resolution statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockerLogResponse {
+    pub blockers: Vec<Blocker>,
+    pub resolution: BlockerResolutionReport,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsensusState {
+    #[schemars(description = "Overall readiness computed from the votes and quorum rule")]
+    pub ready_for_code_changes: bool,
+    #[serde(default)]
+    #[schemars(description = "Open blockers; see the blocker log for their full history")]
+    pub blockers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<String>,
+    #[serde(default)]
+    #[schemars(description = "Latest vote from each role")]
+    pub votes: Vec<ConsensusVote>,
+    #[schemars(description = "Quorum rule used to compute readiness")]
+    pub quorum_rule: QuorumRule,
+    #[serde(default)]
+    #[schemars(description = "Roles that have not voted ready yet")]
+    pub holding_up: Vec<TeamRole>,
+}
+
+impl ConsensusState {
+    fn with_quorum(quorum_rule: QuorumRule) -> Self {
+        let mut state = Self {
+            quorum_rule,
+            ..Self::default()
+        };
+        state.recompute();
+        state
+    }
+
+    /// Records a role's vote, replacing any earlier vote from the same role
+    fn record_vote(&mut self, vote: ConsensusVote) {
+        match self
+            .votes
+            .iter_mut()
+            .find(|existing| existing.role == vote.role)
+        {
+            Some(existing) => *existing = vote,
+            None => self.votes.push(vote),
+        }
+        self.recompute();
+    }
+
+    fn recompute(&mut self) {
+        let (ready, holding_up) = self.quorum_rule.evaluate(&self.votes);
+        self.ready_for_code_changes = ready;
+        self.holding_up = holding_up;
+    }
+
+    /// Describes each role holding up readiness along with its reason
+    fn holding_up_summary(&self) -> String {
+        if self.holding_up.is_empty() {
+            return "nobody".to_string();
+        }
+        self.holding_up
+            .iter()
+            .map(
+                |role| match self.votes.iter().find(|vote| &vote.role == role) {
+                    Some(vote) => {
+                        match vote.rationale.as_deref().filter(|r| !r.trim().is_empty()) {
+                            Some(rationale) => format!("{} (not ready: {})", role, rationale),
+                            None => format!("{} (not ready)", role),
+                        }
+                    }
+                    None => format!("{} (no vote)", role),
+                },
+            )
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ChildRemovalMode {
+    /// Remove every descendant along with the item
+    Cascade,
+    /// Move the children up to the removed item's parent
+    Reparent,
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum BacklogView {
+    #[default]
+    Flat,
+    Grouped,
+}
+
+/// Progress rolled up from an item's descendants
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogProgress {
+    pub total_items: u32,
+    pub done_items: u32,
+    pub total_points: u32,
+    pub done_points: u32,
+    #[schemars(description = "Share of points Done, or of items when nothing is estimated")]
+    pub percent_complete: u32,
+}
+
+impl fmt::Display for BacklogProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}% ({}/{} items, {}/{} points)",
+            self.percent_complete,
+            self.done_items,
+            self.total_items,
+            self.done_points,
+            self.total_points
+        )
+    }
+}
+
+/// Backlog item with its children, for the grouped backlog view
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogNode {
+    pub item: BacklogItem,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<BacklogProgress>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<BacklogNode>,
+}
+
+/// Removes a story and handles its children, returning the resulting changes
+fn remove_from_backlog(
+    backlog: &mut HashMap<String, BacklogItem>,
+    story_id: &str,
+    mode: Option<ChildRemovalMode>,
+) -> Vec<BacklogChange> {
+    let Some(removed) = backlog.remove(story_id) else {
+        return Vec::new();
+    };
+
+    let mut child_ids: Vec<String> = backlog
+        .values()
+        .filter(|item| item.parent_id.as_deref() == Some(story_id))
+        .map(|item| item.id.clone())
+        .collect();
+    child_ids.sort();
+
+    let mut changes = Vec::new();
+    for child_id in child_ids {
+        match mode {
+            Some(ChildRemovalMode::Cascade) => {
+                changes.extend(remove_from_backlog(backlog, &child_id, mode));
+            }
+            Some(ChildRemovalMode::Reparent) | None => {
+                if let Some(child) = backlog.get_mut(&child_id) {
+                    let before = child.clone();
+                    child.parent_id = removed.parent_id.clone();
+                    changes.push(BacklogChange::updated(&before, child.clone()));
+                }
+            }
+        }
+    }
+    changes.insert(0, BacklogChange::new(BacklogChangeType::Removed, removed));
+    changes
+}
+
+/// Finds a loop in the parent chain, returning the item ids along it
+fn find_parent_cycle(backlog: &HashMap<String, BacklogItem>) -> Option<Vec<String>> {
+    let mut ids: Vec<&String> = backlog.keys().collect();
+    ids.sort();
+    for id in ids {
+        let mut chain = vec![id.as_str()];
+        let mut current = backlog.get(id).and_then(|item| item.parent_id.as_deref());
+        while let Some(parent) = current {
+            if let Some(start) = chain.iter().position(|entry| *entry == parent) {
+                let mut cycle: Vec<String> = chain[start..].iter().map(|s| s.to_string()).collect();
+                cycle.push(parent.to_string());
+                return Some(cycle);
+            }
+            chain.push(parent);
+            current = backlog
+                .get(parent)
+                .and_then(|item| item.parent_id.as_deref());
+        }
+    }
+    None
+}
+
+/// A single story field that changed value
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChange {
+    #[schemars(description = "Name of the changed field")]
+    pub field: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Previous value, absent when the field was unset")]
+    pub from: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "New value, absent when the field was cleared")]
+    pub to: Option<String>,
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} -> {}",
+            self.field,
+            self.from.as_deref().unwrap_or("none"),
+            self.to.as_deref().unwrap_or("none")
+        )
+    }
+}
+
+/// Lists every field that differs between two versions of a story
+fn diff_backlog_items(before: &BacklogItem, after: &BacklogItem) -> Vec<FieldChange> {
+    fn joined<T: ToString>(values: &[T]) -> Option<String> {
+        (!values.is_empty()).then(|| {
+            values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+    }
+
+    let mut changes = Vec::new();
+    let mut compare = |field: &str, from: Option<String>, to: Option<String>| {
+        if from != to {
+            changes.push(FieldChange {
+                field: field.to_string(),
+                from,
+                to,
+            });
+        }
+    };
+
+    compare(
+        "title",
+        Some(before.title.clone()),
+        Some(after.title.clone()),
+    );
+    compare(
+        "priority",
+        Some(before.priority.to_string()),
+        Some(after.priority.to_string()),
+    );
+    compare(
+        "status",
+        Some(before.status.to_string()),
+        Some(after.status.to_string()),
+    );
+    compare(
+        "owner",
+        before.owner.as_ref().map(|role| role.to_string()),
+        after.owner.as_ref().map(|role| role.to_string()),
+    );
+    compare(
+        "statusReason",
+        before.status_reason.clone(),
+        after.status_reason.clone(),
+    );
+    compare("notes", before.notes.clone(), after.notes.clone());
+    compare(
+        "dependsOn",
+        joined(&before.depends_on),
+        joined(&after.depends_on),
+    );
+    compare(
+        "storyPoints",
+        before.story_points.map(|points| points.to_string()),
+        after.story_points.map(|points| points.to_string()),
+    );
+    compare(
+        "acceptanceCriteria",
+        joined(&before.acceptance_criteria),
+        joined(&after.acceptance_criteria),
+    );
+    compare(
+        "doneChecklist",
+        joined(&before.done_checklist),
+        joined(&after.done_checklist),
+    );
+    compare(
+        "parentId",
+        before.parent_id.clone(),
+        after.parent_id.clone(),
+    );
+    compare(
+        "itemType",
+        Some(before.item_type.to_string()),
+        Some(after.item_type.to_string()),
+    );
+    compare(
+        "severity",
+        before.severity.map(|severity| severity.to_string()),
+        after.severity.map(|severity| severity.to_string()),
+    );
+    compare(
+        "reproductionSteps",
+        joined(&before.reproduction_steps),
+        joined(&after.reproduction_steps),
+    );
+    compare(
+        "timeboxThoughts",
+        before.timebox_thoughts.map(|timebox| timebox.to_string()),
+        after.timebox_thoughts.map(|timebox| timebox.to_string()),
+    );
+    compare(
+        "expectedOutcome",
+        before.expected_outcome.clone(),
+        after.expected_outcome.clone(),
+    );
+    compare("labels", joined(&before.labels), joined(&after.labels));
+    compare(
+        "scoring",
+        before.scoring.as_ref().map(|scoring| scoring.to_string()),
+        after.scoring.as_ref().map(|scoring| scoring.to_string()),
+    );
+    changes
+}
+
+#[derive(Debug, Clone)]
+struct BacklogChange {
+    change_type: BacklogChangeType,
+    item: BacklogItem,
+    changes: Vec<FieldChange>,
+}
+
+impl BacklogChange {
+    fn new(change_type: BacklogChangeType, item: BacklogItem) -> Self {
+        Self {
+            change_type,
+            item,
+            changes: Vec::new(),
+        }
+    }
+
+    fn updated(before: &BacklogItem, after: BacklogItem) -> Self {
+        Self {
+            change_type: BacklogChangeType::Updated,
+            changes: diff_backlog_items(before, &after),
+            item: after,
+        }
+    }
+
+    fn summary(&self) -> String {
+        match self.change_type {
+            BacklogChangeType::Added => format!(
+                "Added {} [{} | {}]",
+                self.item.id, self.item.priority, self.item.status
+            ),
+            BacklogChangeType::Updated if self.changes.is_empty() => {
+                format!("Updated {}: no field changes", self.item.id)
+            }
+            BacklogChangeType::Updated => format!(
+                "Updated {}: {}",
+                self.item.id,
+                self.changes
+                    .iter()
+                    .map(|change| change.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            BacklogChangeType::Removed => {
+                format!("Removed {} ({})", self.item.id, self.item.title)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BacklogChangeType {
+    Added,
+    Updated,
+    Removed,
+}
+
+/// One recorded change to a story
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryHistoryEntry {
+    #[schemars(description = "Thought number the change was made in")]
+    pub thought_number: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Team role that made the change")]
+    pub role: Option<TeamRole>,
+    pub change_type: BacklogChangeType,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Fields that changed, with old and new values")]
+    pub changes: Vec<FieldChange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Reason given when the status changed")]
+    pub reason: Option<String>,
+    #[schemars(description = "Story as it stood after the change, or before removal")]
+    pub snapshot: BacklogItem,
+    #[schemars(description = "When the change was recorded")]
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Source format for a backlog import
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportFormat {
+    /// Comma-separated values with a header row
+    Csv,
+    /// JSON array of backlog items
+    Json,
+    /// Markdown `- [ ]` / `- [x]` checklist; indented items nest under the item above
+    Markdown,
+}
+
+/// How imported items combine with the existing backlog
+#[derive(
+    Debug, Clone, Copy, Default, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportMode {
+    /// Add new items and overwrite items whose id already exists
+    #[default]
+    Merge,
+    /// Replace the whole backlog with the imported items
+    Replace,
+}
+
+/// Backlog fields that can be mapped to CSV columns
+const CSV_IMPORT_FIELDS: [&str; 12] = [
+    "id",
+    "title",
+    "priority",
+    "status",
+    "owner",
+    "notes",
+    "storyPoints",
+    "labels",
+    "dependsOn",
+    "parentId",
+    "itemType",
+    "statusReason",
+];
+
+/// Parameters for the backlog import tool
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogImportRequest {
+    #[schemars(description = "Format of the content: csv, json or markdown")]
+    pub format: ImportFormat,
+    #[schemars(description = "Text to import")]
+    pub content: String,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    #[schemars(
+        description = "CSV only: maps a backlog field (e.g. storyPoints) to a CSV header; unmapped fields use a header with the field's own name"
+    )]
+    pub columns: HashMap<String, String>,
+    #[serde(default)]
+    #[schemars(description = "merge (default) or replace")]
+    pub mode: ImportMode,
+    #[serde(default)]
+    #[schemars(description = "Report what would change without touching the backlog")]
+    pub dry_run: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Team role performing the import")]
+    pub role: Option<TeamRole>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(
+        description = "Thought the import is recorded under in story history; defaults to the latest thought on the current branch"
+    )]
+    pub thought_number: Option<u32>,
+}
+
+impl BacklogImportRequest {
+    /// Parses the content into items, dropping and reporting repeated ids
+    fn parse(
+        &self,
+        existing: &HashMap<String, BacklogItem>,
+    ) -> Result<(Vec<BacklogItem>, Vec<String>), McpError> {
+        let mut items = match self.format {
+            ImportFormat::Csv => parse_csv_backlog(&self.content, &self.columns)?,
+            ImportFormat::Json => serde_json::from_str(&self.content).map_err(|error| {
+                create_validation_error(&format!(
+                    "content is not a JSON array of backlog items: {}",
+                    error
+                ))
+            })?,
+            ImportFormat::Markdown => {
+                let (mut items, parents) = parse_markdown_backlog(&self.content);
+                assign_import_ids(&mut items, existing);
+                for (index, parent) in parents.into_iter().enumerate() {
+                    items[index].parent_id = parent.map(|parent| items[parent].id.clone());
+                }
+                items
+            }
+        };
+        assign_import_ids(&mut items, existing);
+
+        let mut seen = HashSet::new();
+        let mut duplicate_ids = Vec::new();
+        items.retain(|item| {
+            if seen.insert(item.id.clone()) {
+                return true;
+            }
+            if !duplicate_ids.contains(&item.id) {
+                duplicate_ids.push(item.id.clone());
+            }
+            false
+        });
+        Ok((items, duplicate_ids))
+    }
+}
+
+/// Result of a backlog import or dry run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogImportResponse {
+    pub dry_run: bool,
+    pub mode: ImportMode,
+    pub imported: usize,
+    pub added: Vec<String>,
+    pub updated: Vec<String>,
+    pub removed: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicate_ids: Vec<String>,
+    pub changes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+/// Target format for a backlog export
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, schemars::JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ExportFormat {
+    /// CSV with the same columns the importer reads
+    Csv,
+    /// JSON array of issues for a GitHub bulk import script
+    GithubIssues,
+}
+
+/// Parameters for the backlog export tool
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogExportRequest {
+    #[schemars(description = "Output format: csv or githubIssues")]
+    pub format: ExportFormat,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Only export items matching this filter")]
+    pub filter: Option<BacklogFilter>,
+}
+
+/// Exported backlog text, ready to be written to a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogExportResponse {
+    pub format: ExportFormat,
+    pub items: usize,
+    pub content: String,
+}
+
+/// A backlog item in the shape GitHub's issue API and `gh issue create` expect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GithubIssue {
+    pub title: String,
+    pub body: String,
+    pub labels: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee_role: Option<TeamRole>,
+}
+
+impl From<&BacklogItem> for GithubIssue {
+    fn from(item: &BacklogItem) -> Self {
+        let mut sections = Vec::new();
+        if let Some(notes) = &item.notes {
+            sections.push(notes.clone());
+        }
+        if !item.acceptance_criteria.is_empty() {
+            let criteria = item
+                .acceptance_criteria
+                .iter()
+                .map(|criterion| {
+                    let mark = if criterion.checked { "x" } else { " " };
+                    format!("- [{}] {}", mark, criterion.description)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            sections.push(format!("## Acceptance criteria\n{}", criteria));
+        }
+        if !item.depends_on.is_empty() {
+            sections.push(format!("Depends on: {}", item.depends_on.join(", ")));
+        }
+        sections.push(format!("Backlog id: {}", item.id));
+
+        let serde_name = |value: serde_json::Value| value.as_str().unwrap_or_default().to_string();
+        let mut labels = vec![
+            format!("priority:{}", serde_name(serde_json::json!(item.priority))),
+            format!("status:{}", serde_name(serde_json::json!(item.status))),
+        ];
+        if item.item_type != ItemType::Story {
+            labels.push(format!(
+                "type:{}",
+                serde_name(serde_json::json!(item.item_type))
+            ));
+        }
+        labels.extend(item.labels.iter().cloned());
+
+        Self {
+            title: item.title.clone(),
+            body: sections.join("\n\n"),
+            labels,
+            assignee_role: item.owner.clone(),
+        }
+    }
+}
+
+/// Renders items as CSV using the importer's columns. Only those columns survive an
+/// `importbacklog` round trip: acceptance criteria, the done checklist, scoring inputs
+/// and the bug and spike fields are not exported.
+fn export_csv(items: &[BacklogItem]) -> String {
+    fn quote(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    let mut lines = vec![CSV_IMPORT_FIELDS.join(",")];
+    for item in items {
+        let row = CSV_IMPORT_FIELDS
+            .iter()
+            .map(|field| quote(&exported_field(item, field)))
+            .collect::<Vec<_>>()
+            .join(",");
+        lines.push(row);
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Text form of one backlog field, the inverse of `set_imported_field`
+fn exported_field(item: &BacklogItem, field: &str) -> String {
+    match field {
+        "id" => item.id.clone(),
+        "title" => item.title.clone(),
+        "priority" => item.priority.to_string(),
+        "status" => item.status.to_string(),
+        "owner" => item
+            .owner
+            .as_ref()
+            .map(|owner| owner.to_string())
+            .unwrap_or_default(),
+        "notes" => item.notes.clone().unwrap_or_default(),
+        "storyPoints" => item
+            .story_points
+            .map(|points| points.to_string())
+            .unwrap_or_default(),
+        "labels" => item.labels.join("; "),
+        "dependsOn" => item.depends_on.join("; "),
+        "parentId" => item.parent_id.clone().unwrap_or_default(),
+        "itemType" => item.item_type.to_string(),
+        "statusReason" => item.status_reason.clone().unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+/// Gives items without an id a fresh `IMP-n` id that clashes with nothing else
+fn assign_import_ids(items: &mut [BacklogItem], existing: &HashMap<String, BacklogItem>) {
+    let mut taken: HashSet<String> = existing
+        .keys()
+        .cloned()
+        .chain(items.iter().map(|item| item.id.clone()))
+        .collect();
+    let mut next = 0;
+    for item in items.iter_mut().filter(|item| item.id.trim().is_empty()) {
+        item.id = loop {
+            next += 1;
+            let id = format!("IMP-{}", next);
+            if taken.insert(id.clone()) {
+                break id;
+            }
+        };
+    }
+}
+
+/// Splits CSV text into records, honouring quoted fields with commas, quotes and newlines
+fn parse_csv_records(content: &str) -> Result<Vec<Vec<String>>, McpError> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err(create_validation_error(
+            "content has an unterminated quoted CSV field",
+        ));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records.retain(|record| record.iter().any(|field| !field.trim().is_empty()));
+    Ok(records)
+}
+
+/// Reads CSV rows into backlog items using the header row and the column mapping
+fn parse_csv_backlog(
+    content: &str,
+    columns: &HashMap<String, String>,
+) -> Result<Vec<BacklogItem>, McpError> {
+    let mut records = parse_csv_records(content)?.into_iter();
+    let Some(header) = records.next() else {
+        return Ok(Vec::new());
+    };
+
+    let position = |field: &str| {
+        let name = columns.get(field).map(String::as_str).unwrap_or(field);
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name.trim()))
+    };
+    for (field, column) in columns {
+        if !CSV_IMPORT_FIELDS.contains(&field.as_str()) {
+            return Err(create_validation_error(&format!(
+                "columns.{} is not an importable field; expected one of {}",
+                field,
+                CSV_IMPORT_FIELDS.join(", ")
+            )));
+        }
+        if position(field).is_none() {
+            return Err(create_validation_error(&format!(
+                "columns.{}: CSV header has no column named {}",
+                field, column
+            )));
+        }
+    }
+    if position("title").is_none() {
+        return Err(create_validation_error(
+            "CSV content needs a title column (map one with columns.title)",
+        ));
+    }
+
+    let mapped: Vec<(&str, usize)> = CSV_IMPORT_FIELDS
+        .iter()
+        .filter_map(|field| position(field).map(|column| (*field, column)))
+        .collect();
+    let mut items = Vec::new();
+    for (row, record) in records.enumerate() {
+        let mut item = BacklogItem::default();
+        for (field, column) in &mapped {
+            let value = record.get(*column).map(|value| value.trim()).unwrap_or("");
+            if value.is_empty() {
+                continue;
+            }
+            set_imported_field(&mut item, field, value).map_err(|message| {
+                create_validation_error(&format!("CSV row {}: {}", row + 1, message))
+            })?;
+        }
+        items.push(item);
+    }
+    Ok(items)
+}
+
+/// Sets one backlog field from its text form, as found in a CSV cell
+fn set_imported_field(item: &mut BacklogItem, field: &str, value: &str) -> Result<(), String> {
+    let list = |value: &str| {
+        value
+            .split([';', '|'])
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    match field {
+        "id" => item.id = value.to_string(),
+        "title" => item.title = value.to_string(),
+        "priority" => {
+            let levels = [
+                PriorityLevel::High,
+                PriorityLevel::Medium,
+                PriorityLevel::Low,
+            ];
+            item.priority = parse_variant(field, value, &levels)?;
+        }
+        "status" => item.status = parse_variant(field, value, &StoryStatus::all())?,
+        "owner" => item.owner = Some(parse_variant(field, value, &TeamRole::all())?),
+        "notes" => item.notes = Some(value.to_string()),
+        "storyPoints" => {
+            let points = value
+                .parse()
+                .map_err(|_| format!("storyPoints '{}' is not a whole number", value))?;
+            item.story_points = Some(points);
+        }
+        "labels" => item.labels = list(value),
+        "dependsOn" => item.depends_on = list(value),
+        "parentId" => item.parent_id = Some(value.to_string()),
+        "itemType" => item.item_type = parse_variant(field, value, &ItemType::all())?,
+        "statusReason" => item.status_reason = Some(value.to_string()),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Matches text such as "In Progress" or "in_progress" against an enum's names
+fn parse_variant<T: Clone + fmt::Display + Serialize>(
+    field: &str,
+    value: &str,
+    options: &[T],
+) -> Result<T, String> {
+    fn normalize(text: &str) -> String {
+        text.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    }
+
+    let wanted = normalize(value);
+    options
+        .iter()
+        .find(|option| {
+            let serialized = serde_json::to_value(option).ok();
+            normalize(&option.to_string()) == wanted
+                || serialized
+                    .as_ref()
+                    .and_then(|name| name.as_str())
+                    .is_some_and(|name| normalize(name) == wanted)
+        })
+        .cloned()
+        .ok_or_else(|| {
+            let expected = options
+                .iter()
+                .map(|option| option.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{} '{}' is not one of {}", field, value, expected)
+        })
+}
+
+/// Reads `- [ ]` and `- [x]` lines, returning each item's parent index from its indentation
+fn parse_markdown_backlog(content: &str) -> (Vec<BacklogItem>, Vec<Option<usize>>) {
+    fn looks_like_id(text: &str) -> bool {
+        !text.is_empty()
+            && text.chars().any(|c| c.is_ascii_digit())
+            && text
+                .chars()
+                .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    }
+
+    let mut items = Vec::new();
+    let mut parents = Vec::new();
+    // Indentation and index of the items the next line could nest under
+    let mut open: Vec<(usize, usize)> = Vec::new();
+    for line in content.lines() {
+        let text = line.trim_start();
+        let indent: usize = line[..line.len() - text.len()]
+            .chars()
+            .map(|c| if c == '\t' { 4 } else { 1 })
+            .sum();
+        let Some(rest) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|bullet| text.strip_prefix(bullet))
+        else {
+            continue;
+        };
+        let (done, title) = if let Some(title) = rest.strip_prefix("[ ]") {
+            (false, title.trim())
+        } else if let Some(title) = rest
+            .strip_prefix("[x]")
+            .or_else(|| rest.strip_prefix("[X]"))
+        {
+            (true, title.trim())
+        } else {
+            continue;
+        };
+        if title.is_empty() {
+            continue;
+        }
+        let (id, title) = match title.split_once(':') {
+            Some((id, rest)) if looks_like_id(id) && !rest.trim().is_empty() => {
+                (id.to_string(), rest.trim())
+            }
+            _ => (String::new(), title),
+        };
+
+        while open.last().is_some_and(|(depth, _)| *depth >= indent) {
+            open.pop();
+        }
+        parents.push(open.last().map(|(_, index)| *index));
+        open.push((indent, items.len()));
+        items.push(BacklogItem {
+            id,
+            title: title.to_string(),
+            status: if done {
+                StoryStatus::Done
+            } else {
+                StoryStatus::Todo
+            },
+            ..BacklogItem::default()
+        });
+    }
+    (items, parents)
+}
+
+/// Parameters for the story history tool
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct StoryHistoryRequest {
+    #[serde(rename = "storyId")]
+    #[schemars(description = "Identifier of the story to look up")]
+    pub story_id: String,
+}
+
+/// Full change history for a single story
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoryHistoryResponse {
+    pub story_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<BacklogItem>,
+    pub history: Vec<StoryHistoryEntry>,
+}
+
+#[derive(Debug, Default)]
+struct TeamUpdateOutcome {
+    pm_summary: Option<String>,
+    new_discussion_points: Vec<DiscussionPoint>,
+    backlog_changes: Vec<BacklogChange>,
+    sprint_plan_updated: Option<SprintPlan>,
+    sprint_closed: Option<SprintRecord>,
+    carried_in_story_ids: Vec<String>,
+    new_retro_actions: Vec<RetroAction>,
+    new_decisions: Vec<DecisionRecord>,
+    elicited_answers: Vec<UserQuestion>,
+    consensus_state: Option<ConsensusState>,
+    regressions: Vec<StatusRegression>,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectManagerReport {
+    #[schemars(description = "Structured bullet points shared with the user")]
+    pub bullets: Vec<String>,
+    #[serde(rename = "pmSummary", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Latest project manager summary narrative")]
+    pub pm_summary: Option<String>,
+    #[serde(
+        rename = "newDiscussionPoints",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Discussion points captured during this iteration")]
+    pub new_discussion_points: Vec<DiscussionPoint>,
+    #[serde(
+        rename = "backlogSnapshot",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Current backlog ordered by priority and status")]
+    pub backlog_snapshot: Vec<BacklogItem>,
+    #[serde(rename = "filteredBacklog", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Items matching the request's backlogFilter, in snapshot order")]
+    pub filtered_backlog: Option<Vec<BacklogItem>>,
+    #[serde(rename = "backlogTree", default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Backlog grouped by epic, when backlogView is grouped")]
+    pub backlog_tree: Vec<BacklogNode>,
+    #[serde(rename = "activeSprint", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Active sprint plan the team is executing")]
+    pub active_sprint: Option<SprintPlan>,
+    #[serde(rename = "sprintClosed", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Sprint closed during this iteration")]
+    pub sprint_closed: Option<SprintRecord>,
+    #[serde(
+        rename = "pendingCarryOver",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Stories waiting to carry over into the next sprint")]
+    pub pending_carry_over: Vec<String>,
+    #[serde(
+        rename = "openRetroActions",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Retrospective actions that are still open")]
+    pub open_retro_actions: Vec<RetroAction>,
+    #[serde(
+        rename = "newDecisions",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Decisions recorded during this iteration")]
+    pub new_decisions: Vec<DecisionRecord>,
+    #[serde(
+        rename = "openQuestions",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Questions still waiting for the user's answer")]
+    pub open_questions: Vec<UserQuestion>,
+    #[serde(rename = "blockerResolution", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Open blockers and how long resolved ones took")]
+    pub blocker_resolution: Option<BlockerResolutionReport>,
+    #[serde(rename = "topRisks", default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Open and mitigating risks with the highest exposure")]
+    pub top_risks: Vec<Risk>,
+    #[serde(rename = "burndown", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Burndown series for the active sprint")]
+    pub burndown: Option<BurndownReport>,
+    #[serde(rename = "velocity", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Rolling velocity across closed sprints")]
+    pub velocity: Option<VelocityReport>,
+    #[serde(rename = "consensus")]
+    #[schemars(description = "Latest consensus state for moving forward")]
+    pub consensus: ConsensusState,
+    #[serde(rename = "waitingOnUser")]
+    #[schemars(description = "Whether the team is awaiting input from the user")]
+    pub waiting_on_user: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Problems the project manager wants the team to address")]
+    pub warnings: Vec<String>,
+    #[serde(rename = "workByType", default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Backlog item counts and points per item type")]
+    pub work_by_type: Vec<TypeBreakdown>,
+    #[schemars(description = "Scheme currently used to rank the backlog")]
+    pub prioritization: PrioritizationScheme,
+    #[serde(rename = "valueScores", default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "RICE or WSJF scores for scored items, in backlog order")]
+    pub value_scores: Vec<ValueScore>,
+    #[serde(rename = "sprintPoints", skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Planned versus completed points for the active sprint")]
+    pub sprint_points: Option<SprintPoints>,
+    #[serde(
+        rename = "definitionOfDone",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    #[schemars(description = "Team-wide checklist every story must confirm before Done")]
+    pub definition_of_done: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schemars(description = "Stories that moved backwards in the delivery flow this iteration")]
+    pub regressions: Vec<StatusRegression>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TeamState {
+    config: TeamConfig,
+    pm_summaries: Vec<String>,
+    discussion_log: Vec<DiscussionPoint>,
+    backlog: HashMap<String, BacklogItem>,
+    active_sprint: Option<ActiveSprint>,
+    sprint_history: Vec<SprintRecord>,
+    pending_carry_over: Vec<String>,
+    retro_actions: Vec<RetroAction>,
+    consensus: ConsensusState,
+    definition_of_done: Vec<String>,
+    prioritization: PrioritizationScheme,
+    story_history: HashMap<String, Vec<StoryHistoryEntry>>,
+    decisions: Vec<DecisionRecord>,
+    risks: Vec<Risk>,
+    blockers: Vec<Blocker>,
+    questions: Vec<UserQuestion>,
+}
+
+impl TeamState {
+    fn with_config(config: TeamConfig) -> Self {
+        Self {
+            consensus: ConsensusState::with_quorum(config.quorum),
+            definition_of_done: config.definition_of_done.clone(),
+            prioritization: config.prioritization,
+            config,
+            ..Self::default()
+        }
+    }
+
+    fn permissions(&self) -> &PermissionMatrix {
+        &self.config.permissions
+    }
+
+    /// Validates the request against the current team state
+    fn validate_request(&self, request: &DeliberateThinkingRequest) -> Result<(), McpError> {
+        self.validate_sprint_lifecycle(request)?;
+        self.validate_decisions(request)?;
+        self.validate_risks(request)?;
+        self.validate_blockers(request)?;
+
+        if request.backlog_stories.is_empty()
+            && request.backlog_patches.is_empty()
+            && request.remove_story_ids.is_empty()
+        {
+            return Ok(());
+        }
+
+        let backlog = self.projected_backlog(request)?;
+        if let Some(cycle) = find_dependency_cycle(&backlog) {
+            return Err(create_validation_error(&format!(
+                "backlogStories.dependsOn would create a dependency cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        if let Some(cycle) = find_parent_cycle(&backlog) {
+            return Err(create_validation_error(&format!(
+                "parentId would create a parent cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let definition_of_done = request
+            .definition_of_done
+            .as_deref()
+            .unwrap_or(&self.definition_of_done);
+        let touched = request
+            .backlog_stories
+            .iter()
+            .map(|story| &story.id)
+            .chain(request.backlog_patches.iter().map(|patch| &patch.id));
+        for story_id in touched {
+            if let Some(story) = backlog.get(story_id) {
+                self.validate_story_change(story, &backlog, definition_of_done)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks a new or changed story against the projected backlog, the workflow and
+    /// the definition of done
+    fn validate_story_change(
+        &self,
+        story: &BacklogItem,
+        backlog: &HashMap<String, BacklogItem>,
+        definition_of_done: &[String],
+    ) -> Result<(), McpError> {
+        story.validate_type_fields()?;
+        if let Some(scoring) = &story.scoring {
+            scoring.validate(&story.id)?;
+        }
+        if let Some(parent_id) = &story.parent_id {
+            if !backlog.contains_key(parent_id) {
+                return Err(create_validation_error(&format!(
+                    "{} has parentId {}, which does not match a backlog item",
+                    story.id, parent_id
+                )));
+            }
+        }
+        let previous = self.backlog.get(&story.id);
+        if self.config.workflow.enforcement == WorkflowEnforcement::Reject {
+            if let Some(violation) = previous
+                .and_then(|previous| self.config.workflow.violation(&previous.status, story))
+            {
+                return Err(create_validation_error(&violation));
+            }
+        }
+        let was_done = previous.is_some_and(|existing| existing.status == StoryStatus::Done);
+        if story.status != StoryStatus::Done || was_done {
+            return Ok(());
+        }
+        let gaps = story.done_gaps(definition_of_done);
+        if !gaps.is_empty() {
+            return Err(create_validation_error(&format!(
+                "{} cannot move to Done: {}",
+                story.id,
+                gaps.join("; ")
+            )));
+        }
+        Ok(())
+    }
+
+    fn queue_question(
+        &mut self,
+        request: &DeliberateThinkingRequest,
+        question: &QuestionInput,
+    ) -> String {
+        let id = format!("Q-{}", self.questions.len() + 1);
+        self.questions.push(UserQuestion {
+            id: id.clone(),
+            question: question.question.clone(),
+            asked_by: request.role.clone(),
+            options: question.options.clone(),
+            blocking: question.blocking,
+            asked_at_thought: request.thought_number,
+            answer: None,
+            answered_at: None,
+            dismissed_at_thought: None,
+        });
+        id
+    }
+
+    /// `requiresUserInput: false`: the team no longer waits on its open blocking questions
+    fn withdraw_blocking_questions(&mut self, thought_number: u32) {
+        for question in &mut self.questions {
+            if question.blocking && question.answer.is_none() {
+                question.dismissed_at_thought.get_or_insert(thought_number);
+            }
+        }
+    }
+
+    /// `requiresUserInput: true`: queues the thought as a blocking question unless one
+    /// is already open, returning the new question's id
+    fn queue_thought_question(&mut self, request: &DeliberateThinkingRequest) -> Option<String> {
+        let thought = request.thought.trim();
+        if self.waiting_on_user() || thought.is_empty() {
+            return None;
+        }
+        let question = QuestionInput {
+            question: thought.to_string(),
+            options: Vec::new(),
+            blocking: true,
+        };
+        Some(self.queue_question(request, &question))
+    }
+
+    /// Handles `requiresUserInput: true`. Without elicitation the thought is queued as a
+    /// blocking question; with it, the returned form covers every open question plus
+    /// the thought itself when nothing blocking is open, and nothing is queued until
+    /// the user answers.
+    fn request_user_input(
+        &mut self,
+        request: &DeliberateThinkingRequest,
+        can_elicit: bool,
+    ) -> Option<CreateElicitationRequestParam> {
+        if request.requires_user_input != Some(true) {
+            return None;
+        }
+        if !can_elicit {
+            self.queue_thought_question(request);
+            return None;
+        }
+
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+        let mut questions = Vec::new();
+        let thought = request.thought.trim();
+        if !self.waiting_on_user() && !thought.is_empty() {
+            properties.insert(
+                THOUGHT_ANSWER_KEY.to_string(),
+                serde_json::json!({
+                    "type": "string",
+                    "title": "Answer",
+                    "description": thought,
+                }),
+            );
+            required.push(THOUGHT_ANSWER_KEY.to_string());
+            questions.push(thought);
+        }
+        let open = self.open_questions();
+        for question in &open {
+            // Options are only suggestions, so they go in the description rather than an
+            // `enum` that would stop the user answering freely
+            let description = if question.options.is_empty() {
+                question.question.clone()
+            } else {
+                format!(
+                    "{} (suggested: {})",
+                    question.question,
+                    question.options.join(", ")
+                )
+            };
+            properties.insert(
+                question.id.clone(),
+                serde_json::json!({
+                    "type": "string",
+                    "title": question.id,
+                    "description": description,
+                }),
+            );
+            if question.blocking {
+                required.push(question.id.clone());
+            }
+            questions.push(question.question.as_str());
+        }
+        if questions.is_empty() {
+            return None;
+        }
+        let schema = serde_json::json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+        });
+
+        Some(CreateElicitationRequestParam {
+            message: format!("The team needs your input: {}", questions.join(" / ")),
+            requested_schema: schema.as_object().cloned().unwrap_or_default(),
+        })
+    }
+
+    /// Records answers returned by an elicitation, keyed by question id; fields the form
+    /// did not ask for are ignored. An answer to the thought itself queues the thought
+    /// as a question and answers it. The lock is released while the user answers, so
+    /// answers that other calls overtook are reported as warnings.
+    fn record_elicited_answers(
+        &mut self,
+        request: &DeliberateThinkingRequest,
+        form: &CreateElicitationRequestParam,
+        content: &serde_json::Value,
+        outcome: &mut TeamUpdateOutcome,
+    ) {
+        let Some(answers) = content.as_object() else {
+            return;
+        };
+        let requested = form
+            .requested_schema
+            .get("properties")
+            .and_then(|properties| properties.as_object());
+
+        for (key, answer) in answers {
+            if !requested.is_some_and(|properties| properties.contains_key(key)) {
+                continue;
+            }
+            let answer = match answer {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            let question_id = if key == THOUGHT_ANSWER_KEY {
+                match self.queue_thought_question(request) {
+                    Some(question_id) => question_id,
+                    None => {
+                        outcome.warnings.push(format!(
+                            "Answer to thought {} not recorded: another blocking question was queued while the user answered ({})",
+                            request.thought_number, answer
+                        ));
+                        continue;
+                    }
+                }
+            } else {
+                key.clone()
+            };
+            let request = AnswerQuestionRequest {
+                question_id,
+                answer,
+            };
+            match self.answer_question(&request) {
+                Ok(response) => outcome.elicited_answers.push(response.question),
+                Err(error) => outcome
+                    .warnings
+                    .push(format!("Elicited answer not recorded: {}", error.message)),
+            }
+        }
+    }
+
+    fn open_questions(&self) -> Vec<&UserQuestion> {
+        self.questions
+            .iter()
+            .filter(|question| question.answer.is_none() && question.dismissed_at_thought.is_none())
+            .collect()
+    }
+
+    /// Waiting while any blocking question is open
+    fn waiting_on_user(&self) -> bool {
+        self.open_questions()
+            .iter()
+            .any(|question| question.blocking)
+    }
+
+    /// Records the user's answer and links it into the discussion log
+    fn answer_question(
+        &mut self,
+        request: &AnswerQuestionRequest,
+    ) -> Result<AnswerQuestionResponse, McpError> {
+        validate_non_empty("answer", &request.answer)?;
+        let Some(question) = self
+            .questions
+            .iter_mut()
+            .find(|question| question.id == request.question_id)
+        else {
+            return Err(create_validation_error(&format!(
+                "questionId: unknown question {}",
+                request.question_id
+            )));
+        };
+        if question.answer.is_some() {
+            return Err(create_validation_error(&format!(
+                "questionId: {} has already been answered",
+                request.question_id
+            )));
+        }
+
+        let answer = request.answer.trim().to_string();
+        question.answer = Some(answer.clone());
+        question.answered_at = Some(Utc::now());
+        let question = question.clone();
+        self.discussion_log.push(DiscussionPoint {
+            role: Speaker::User,
+            detail: format!("Answered '{}': {}", question.question, answer),
+            question_id: Some(question.id.clone()),
+        });
+
+        Ok(AnswerQuestionResponse {
+            question,
+            waiting_on_user: self.waiting_on_user(),
+            open_questions: self.open_questions().into_iter().cloned().collect(),
+        })
+    }
+
+    fn validate_blockers(&self, request: &DeliberateThinkingRequest) -> Result<(), McpError> {
+        for update in &request.blockers {
+            for story_id in update.story_ids.iter().flatten() {
+                let known = self.backlog.contains_key(story_id)
+                    || request
+                        .backlog_stories
+                        .iter()
+                        .any(|story| &story.id == story_id);
+                if !known {
+                    return Err(create_validation_error(&format!(
+                        "blockers.storyIds: {} does not match a backlog item",
+                        story_id
+                    )));
+                }
+            }
+
+            let Some(id) = &update.id else {
+                continue;
+            };
+            let Some(blocker) = self.blockers.iter().find(|blocker| &blocker.id == id) else {
+                return Err(create_validation_error(&format!(
+                    "blockers.id: unknown blocker {}",
+                    id
+                )));
+            };
+            match (update.status, blocker.status) {
+                (Some(BlockerStatus::Resolved), BlockerStatus::Resolved) => {
+                    return Err(create_validation_error(&format!(
+                        "blockers: {} is already resolved",
+                        id
+                    )));
+                }
+                (Some(BlockerStatus::Open), BlockerStatus::Open) => {
+                    return Err(create_validation_error(&format!(
+                        "blockers: {} is already open",
+                        id
+                    )));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Opens a new blocker unless an open one already has the same description
+    fn raise_blocker(
+        &mut self,
+        request: &DeliberateThinkingRequest,
+        description: &str,
+    ) -> Option<&mut Blocker> {
+        let description = description.trim();
+        let duplicate = self
+            .blockers
+            .iter()
+            .any(|blocker| blocker.is_open_duplicate(description));
+        if description.is_empty() || duplicate {
+            return None;
+        }
+
+        self.blockers.push(Blocker {
+            id: format!("BLK-{}", self.blockers.len() + 1),
+            description: description.to_string(),
+            owner: None,
+            story_ids: Vec::new(),
+            status: BlockerStatus::Open,
+            raised_by: request.role.clone(),
+            raised_at_thought: request.thought_number,
+            reopened_at_thought: None,
+            resolved_at_thought: None,
+            resolution: None,
+        });
+        self.blockers.last_mut()
+    }
+
+    /// Summarises open blockers and how long resolved ones took, as of a thought
+    fn blocker_resolution(&self, thought_number: u32) -> BlockerResolutionReport {
+        let durations: Vec<u32> = self
+            .blockers
+            .iter()
+            .filter_map(Blocker::resolution_thoughts)
+            .collect();
+        let oldest_open = self
+            .blockers
+            .iter()
+            .filter(|blocker| blocker.status == BlockerStatus::Open)
+            .min_by_key(|blocker| blocker.opened_at_thought());
+
+        BlockerResolutionReport {
+            open: self
+                .blockers
+                .iter()
+                .filter(|blocker| blocker.status == BlockerStatus::Open)
+                .count(),
+            resolved: durations.len(),
+            average_resolution_thoughts: (!durations.is_empty()).then(|| {
+                durations
+                    .iter()
+                    .map(|&thoughts| f64::from(thoughts))
+                    .sum::<f64>()
+                    / durations.len() as f64
+            }),
+            longest_resolution_thoughts: durations.iter().copied().max(),
+            oldest_open: oldest_open.map(|blocker| blocker.id.clone()),
+            oldest_open_thoughts: oldest_open
+                .map(|blocker| thought_number.saturating_sub(blocker.opened_at_thought())),
+        }
+    }
+
+    fn validate_risks(&self, request: &DeliberateThinkingRequest) -> Result<(), McpError> {
+        for update in &request.risks {
+            if let Some(id) = &update.id {
+                if !self.risks.iter().any(|risk| &risk.id == id) {
+                    return Err(create_validation_error(&format!(
+                        "risks.id: unknown risk {}",
+                        id
+                    )));
+                }
+            }
+            for story_id in update.story_ids.iter().flatten() {
+                let known = self.backlog.contains_key(story_id)
+                    || request
+                        .backlog_stories
+                        .iter()
+                        .any(|story| &story.id == story_id);
+                if !known {
+                    return Err(create_validation_error(&format!(
+                        "risks.storyIds: {} does not match a backlog item",
+                        story_id
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a new risk or applies an update to a registered one
+    fn update_risk(&mut self, thought_number: u32, update: &RiskUpdate) {
+        if let Some(id) = &update.id {
+            if let Some(risk) = self.risks.iter_mut().find(|risk| &risk.id == id) {
+                risk.apply(update);
+            }
+            return;
+        }
+
+        let mut risk = Risk {
+            id: format!("RISK-{}", self.risks.len() + 1),
+            description: String::new(),
+            likelihood: RiskLevel::default(),
+            impact: RiskLevel::default(),
+            exposure: 0,
+            mitigation: None,
+            owner: None,
+            status: RiskStatus::default(),
+            story_ids: Vec::new(),
+            raised_at_thought: thought_number,
+        };
+        risk.apply(update);
+        self.risks.push(risk);
+    }
+
+    /// Moves free-text sprint plan risks into the register, skipping ones already there
+    fn register_sprint_risks(&mut self, thought_number: u32, plan: &SprintPlan) {
+        for description in &plan.risks {
+            let description = description.trim();
+            let known = self
+                .risks
+                .iter()
+                .any(|risk| risk.description.eq_ignore_ascii_case(description));
+            if description.is_empty() || known {
+                continue;
+            }
+            let update = RiskUpdate {
+                description: Some(description.to_string()),
+                ..RiskUpdate::default()
+            };
+            self.update_risk(thought_number, &update);
+        }
+    }
+
+    /// Registered risks sorted by exposure, highest first
+    fn risks_by_exposure(&self) -> Vec<&Risk> {
+        let mut risks: Vec<&Risk> = self.risks.iter().collect();
+        risks.sort_by(|a, b| {
+            b.exposure
+                .cmp(&a.exposure)
+                .then_with(|| b.is_active().cmp(&a.is_active()))
+                .then_with(|| a.raised_at_thought.cmp(&b.raised_at_thought))
+        });
+        risks
+    }
+
+    fn validate_decisions(&self, request: &DeliberateThinkingRequest) -> Result<(), McpError> {
+        let mut superseded = HashSet::new();
+        for id in request
+            .decisions
+            .iter()
+            .filter_map(|decision| decision.supersedes.as_ref())
+        {
+            let Some(record) = self.decisions.iter().find(|record| &record.id == id) else {
+                return Err(create_validation_error(&format!(
+                    "decisions.supersedes: unknown decision {}",
+                    id
+                )));
+            };
+            if record.status == DecisionStatus::Superseded || !superseded.insert(id) {
+                return Err(create_validation_error(&format!(
+                    "decisions.supersedes: {} has already been superseded",
+                    id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds a decision to the log, marking any decision it replaces as superseded
+    fn record_decision(
+        &mut self,
+        request: &DeliberateThinkingRequest,
+        decision: &DecisionInput,
+    ) -> DecisionRecord {
+        let id = format!("ADR-{:04}", self.decisions.len() + 1);
+        if let Some(previous) = decision.supersedes.as_ref().and_then(|superseded| {
+            self.decisions
+                .iter_mut()
+                .find(|record| &record.id == superseded)
+        }) {
+            previous.status = DecisionStatus::Superseded;
+            previous.superseded_by = Some(id.clone());
+        }
+
+        let deciding_roles = if decision.deciding_roles.is_empty() {
+            request.role.iter().cloned().collect()
+        } else {
+            decision.deciding_roles.clone()
+        };
+        let record = DecisionRecord {
+            id,
+            title: decision.title.clone(),
+            context: decision.context.clone(),
+            options: decision.options.clone(),
+            chosen_option: decision.chosen_option.clone(),
+            consequences: decision.consequences.clone(),
+            deciding_roles,
+            thought_number: request.thought_number,
+            decided_at: Utc::now(),
+            status: DecisionStatus::Accepted,
+            supersedes: decision.supersedes.clone(),
+            superseded_by: None,
+        };
+        self.decisions.push(record.clone());
+        record
+    }
+
+    /// Returns the decision log, optionally rendered as ADR Markdown files
+    fn decision_log(&self, request: &DecisionLogRequest) -> DecisionLogResponse {
+        let decisions: Vec<DecisionRecord> = self
+            .decisions
+            .iter()
+            .filter(|record| !request.accepted_only || record.status == DecisionStatus::Accepted)
+            .cloned()
+            .collect();
+        let files = match request.format {
+            DecisionLogFormat::Records => Vec::new(),
+            DecisionLogFormat::AdrMarkdown => decisions
+                .iter()
+                .map(|record| AdrFile {
+                    file_name: record.file_name(),
+                    content: record.to_markdown(&self.decisions),
+                })
+                .collect(),
+        };
+        DecisionLogResponse { decisions, files }
+    }
+
+    /// Stops sprint plans from silently replacing a sprint that is still running
+    fn validate_sprint_lifecycle(
+        &self,
+        request: &DeliberateThinkingRequest,
+    ) -> Result<(), McpError> {
+        self.validate_retrospective(request)?;
+
+        if request.close_sprint.is_some() && self.active_sprint.is_none() {
+            return Err(create_validation_error(
+                "closeSprint: there is no active sprint to close",
+            ));
+        }
+
+        // Closing happens first, so a request may close one sprint and start the next
+        let active = self
+            .active_plan()
+            .filter(|_| request.close_sprint.is_none());
+        let Some(active) = active else {
+            return Ok(());
+        };
+
+        if request.start_sprint.is_some() {
+            return Err(create_validation_error(&format!(
+                "startSprint: {} is still active; close it first",
+                active.sprint_name
+            )));
+        }
+
+        if let Some(plan) = &request.sprint_plan {
+            if plan.sprint_name != active.sprint_name {
+                return Err(create_validation_error(&format!(
+                    "sprintPlan: {} is still active; close it before planning {}",
+                    active.sprint_name, plan.sprint_name
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_retrospective(&self, request: &DeliberateThinkingRequest) -> Result<(), McpError> {
+        if !request.retrospective.is_empty()
+            && self.sprint_history.is_empty()
+            && request.close_sprint.is_none()
+        {
+            return Err(create_validation_error(
+                "retrospective: there is no closed sprint to attach it to",
+            ));
+        }
+
+        // Re-sent entries may name the stories their actions already created
+        let retro_sprint = match request.close_sprint {
+            Some(_) => None,
+            None => self
+                .sprint_history
+                .last()
+                .map(|record| record.plan.sprint_name.as_str()),
+        };
+        let mut new_story_ids = HashSet::new();
+        let action_story_ids = request
+            .retrospective
+            .iter()
+            .flat_map(|entry| &entry.action_items)
+            .filter_map(|action| action.story_id.as_ref());
+        for story_id in action_story_ids {
+            let filed = self.retro_actions.iter().any(|action| {
+                Some(action.sprint_name.as_str()) == retro_sprint
+                    && action.story_id.as_ref() == Some(story_id)
+            });
+            let taken = (self.backlog.contains_key(story_id) && !filed)
+                || request
+                    .backlog_stories
+                    .iter()
+                    .any(|story| &story.id == story_id);
+            if taken || !new_story_ids.insert(story_id) {
+                return Err(create_validation_error(&format!(
+                    "retrospective.actionItems.storyId {} is already in use",
+                    story_id
+                )));
+            }
+        }
+
+        for action_id in &request.resolve_retro_action_ids {
+            if !self
+                .retro_actions
+                .iter()
+                .any(|action| &action.id == action_id)
+            {
+                return Err(create_validation_error(&format!(
+                    "resolveRetroActionIds: unknown retro action {}",
+                    action_id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn active_plan(&self) -> Option<&SprintPlan> {
+        self.active_sprint.as_ref().map(|sprint| &sprint.plan)
+    }
+
+    /// Backlog as it would look after applying the request's story changes
+    fn projected_backlog(
+        &self,
+        request: &DeliberateThinkingRequest,
+    ) -> Result<HashMap<String, BacklogItem>, McpError> {
+        let mut backlog = self.backlog.clone();
+        for story in &request.backlog_stories {
+            backlog.insert(story.id.clone(), story.clone());
+        }
+        for patch in &request.backlog_patches {
+            let existing = backlog.get(&patch.id).ok_or_else(|| {
+                create_validation_error(&format!(
+                    "backlogPatches.id {} does not match an existing story",
+                    patch.id
+                ))
+            })?;
+            let patched = patch.apply(existing)?;
+            backlog.insert(patch.id.clone(), patched);
+        }
+        if request.remove_children.is_none() {
+            for story_id in &request.remove_story_ids {
+                let mut children: Vec<&str> = backlog
+                    .values()
+                    .filter(|item| item.parent_id.as_ref() == Some(story_id))
+                    .filter(|item| !request.remove_story_ids.contains(&item.id))
+                    .map(|item| item.id.as_str())
+                    .collect();
+                if children.is_empty() {
+                    continue;
+                }
+                children.sort();
+                return Err(create_validation_error(&format!(
+                    "removeStoryIds: {} has children {}; set removeChildren to cascade or reparent",
+                    story_id,
+                    children.join(", ")
+                )));
+            }
+        }
+        for story_id in &request.remove_story_ids {
+            remove_from_backlog(&mut backlog, story_id, request.remove_children);
+        }
+        Ok(backlog)
+    }
+
+    fn process_request(&mut self, request: &DeliberateThinkingRequest) -> TeamUpdateOutcome {
+        let mut outcome = TeamUpdateOutcome::default();
+
+        if !request.discussion_points.is_empty() {
+            for point in &request.discussion_points {
+                self.discussion_log.push(point.clone());
+            }
+            outcome
+                .new_discussion_points
+                .extend(request.discussion_points.clone());
+        }
+
+        if let Some(role) = &request.role {
+            if matches!(role, TeamRole::ProjectManager) {
+                let summary = request.thought.trim();
+                if !summary.is_empty() {
+                    let summary = summary.to_string();
+                    self.pm_summaries.push(summary.clone());
+                    outcome.pm_summary = Some(summary);
+                }
+            } else if outcome.new_discussion_points.is_empty() {
+                let note = request.thought.trim();
+                if !note.is_empty() {
+                    let derived = DiscussionPoint {
+                        role: Speaker::Role(role.clone()),
+                        detail: note.to_string(),
+                        question_id: None,
+                    };
+                    self.discussion_log.push(derived.clone());
+                    outcome.new_discussion_points.push(derived);
+                }
+            }
+        }
+
+        for story in &request.backlog_stories {
+            self.upsert_story(story.clone(), &mut outcome);
+        }
+
+        for patch in &request.backlog_patches {
+            // Validation already confirmed every patch applies cleanly
+            let patched = self
+                .backlog
+                .get(&patch.id)
+                .and_then(|existing| patch.apply(existing).ok());
+            if let Some(patched) = patched {
+                self.upsert_story(patched, &mut outcome);
+            }
+        }
+
+        for story_id in &request.remove_story_ids {
+            let changes = remove_from_backlog(&mut self.backlog, story_id, request.remove_children);
+            outcome.backlog_changes.extend(changes);
+        }
+
+        if let Some(closure) = &request.close_sprint {
+            outcome.sprint_closed = self.close_sprint(request.thought_number, closure);
+        }
+
+        if !request.retrospective.is_empty() {
+            self.record_retrospective(&request.retrospective, &mut outcome);
+        }
+
+        for action_id in &request.resolve_retro_action_ids {
+            if let Some(action) = self
+                .retro_actions
+                .iter_mut()
+                .find(|action| &action.id == action_id)
+            {
+                action.status = RetroActionStatus::Done;
+            }
+        }
+
+        if let Some(plan) = request
+            .start_sprint
+            .as_ref()
+            .or(request.sprint_plan.as_ref())
+        {
+            let plan = match &mut self.active_sprint {
+                Some(active) => {
+                    active.plan = SprintPlan {
+                        retro_action_ids: std::mem::take(&mut active.plan.retro_action_ids),
+                        ..plan.clone()
+                    };
+                    active.plan.clone()
+                }
+                None => self.start_sprint(request.thought_number, plan, &mut outcome),
+            };
+            self.register_sprint_risks(request.thought_number, &plan);
+            outcome.sprint_plan_updated = Some(plan);
+        }
+
+        for update in &request.risks {
+            self.update_risk(request.thought_number, update);
+        }
+
+        if let Some(update) = &request.consensus_update {
+            if let Some(role) = &request.role {
+                self.consensus.record_vote(ConsensusVote {
+                    role: role.clone(),
+                    ready: update.ready_for_code_changes,
+                    rationale: update.rationale.clone(),
+                    thought_number: request.thought_number,
+                });
+            }
+            let can_update = self
+                .permissions()
+                .check(TeamAction::UpdateConsensus, request.role.as_ref())
+                .is_ok();
+            if can_update {
+                for description in &update.blockers {
+                    self.raise_blocker(request, description);
+                }
+                self.consensus.notes = update.notes.clone();
+            }
+        }
+
+        for update in &request.blockers {
+            match &update.id {
+                Some(id) => {
+                    if let Some(blocker) =
+                        self.blockers.iter_mut().find(|blocker| &blocker.id == id)
+                    {
+                        blocker.apply(request.thought_number, update);
+                    }
+                }
+                None => {
+                    let description = update.description.clone().unwrap_or_default();
+                    let description = description.trim();
+                    if let Some(existing) = self
+                        .blockers
+                        .iter_mut()
+                        .find(|blocker| blocker.is_open_duplicate(description))
+                    {
+                        outcome.warnings.extend(existing.merge(update));
+                    } else if let Some(blocker) = self.raise_blocker(request, description) {
+                        blocker.apply(request.thought_number, update);
+                    }
+                }
+            }
+        }
+
+        if request.consensus_update.is_some() || !request.blockers.is_empty() {
+            self.consensus.blockers = self
+                .blockers
+                .iter()
+                .filter(|blocker| blocker.status == BlockerStatus::Open)
+                .map(|blocker| blocker.to_string())
+                .collect();
+            outcome.consensus_state = Some(self.consensus.clone());
+        }
+
+        if request.requires_user_input == Some(false) {
+            self.withdraw_blocking_questions(request.thought_number);
+        }
+        for question in &request.questions {
+            self.queue_question(request, question);
+        }
+
+        if let Some(checklist) = &request.definition_of_done {
+            self.definition_of_done = checklist.clone();
+        }
+
+        if let Some(scheme) = request.prioritization {
+            self.prioritization = scheme;
+        }
+
+        for decision in &request.decisions {
+            let record = self.record_decision(request, decision);
+            outcome.new_decisions.push(record);
+        }
+
+        self.record_history(
+            request.thought_number,
+            request.role.as_ref(),
+            &outcome.backlog_changes,
+        );
+        self.record_burndown(request.thought_number);
+
+        outcome
+    }
+
+    /// Attaches retrospective entries to the last closed sprint and files their actions.
+    /// Action items already filed for that sprint are updated rather than duplicated.
+    fn record_retrospective(&mut self, entries: &[RetroEntry], outcome: &mut TeamUpdateOutcome) {
+        let Some(sprint_name) = self
+            .sprint_history
+            .last()
+            .map(|record| record.plan.sprint_name.clone())
+        else {
+            return;
+        };
+
+        for entry in entries {
+            for action in &entry.action_items {
+                if let Some(existing) = self
+                    .retro_actions
+                    .iter_mut()
+                    .find(|existing| existing.matches(&sprint_name, action))
+                {
+                    if action.owner.is_some() {
+                        existing.owner = action.owner.clone();
+                    }
+                    continue;
+                }
+
+                if let Some(story_id) = &action.story_id {
+                    let story = BacklogItem {
+                        id: story_id.clone(),
+                        title: action.description.clone(),
+                        owner: action.owner.clone(),
+                        notes: Some(format!("Retrospective action from {}", sprint_name)),
+                        ..BacklogItem::default()
+                    };
+                    self.upsert_story(story, outcome);
+                }
+                let retro_action = RetroAction {
+                    id: format!("RA-{}", self.retro_actions.len() + 1),
+                    description: action.description.trim().to_string(),
+                    owner: action.owner.clone(),
+                    sprint_name: sprint_name.clone(),
+                    story_id: action.story_id.clone(),
+                    status: RetroActionStatus::Open,
+                };
+                outcome.new_retro_actions.push(retro_action.clone());
+                self.retro_actions.push(retro_action);
+            }
+
+            if let Some(record) = self.sprint_history.last_mut() {
+                match record
+                    .retrospective
+                    .iter_mut()
+                    .find(|existing| existing.role == entry.role)
+                {
+                    Some(existing) => *existing = entry.clone(),
+                    None => record.retrospective.push(entry.clone()),
+                }
+            }
+        }
+    }
+
+    /// Open retro actions; story-backed actions stay open until their story is Done
+    fn open_retro_actions(&self) -> Vec<&RetroAction> {
+        self.retro_actions
+            .iter()
+            .filter(|action| action.status == RetroActionStatus::Open)
+            .filter(|action| {
+                action.story_id.as_ref().is_none_or(|story_id| {
+                    self.backlog
+                        .get(story_id)
+                        .is_some_and(|story| story.status != StoryStatus::Done)
+                })
+            })
+            .collect()
+    }
+
+    /// Samples the active sprint's remaining work for its burndown series
+    fn record_burndown(&mut self, thought_number: u32) {
+        let Some(plan) = self.active_plan() else {
+            return;
+        };
+        let points = self.sprint_points(plan);
+        let point = BurndownPoint {
+            thought_number,
+            recorded_at: Utc::now(),
+            remaining_points: points.planned_points - points.completed_points,
+            completed_points: points.completed_points,
+        };
+        if let Some(active) = &mut self.active_sprint {
+            active.record_burndown(point);
+        }
+    }
+
+    fn burndown(&self) -> Option<BurndownReport> {
+        let active = self.active_sprint.as_ref()?;
+        let points = self.sprint_points(&active.plan);
+        Some(BurndownReport::new(
+            &active.plan.sprint_name,
+            points.planned_points,
+            &active.burndown,
+        ))
+    }
+
+    /// Average completed points over the most recently closed sprints
+    fn velocity(&self) -> Option<VelocityReport> {
+        let window = self.config.velocity_window.max(1);
+        let start = self.sprint_history.len().saturating_sub(window);
+        let sprints: Vec<SprintVelocity> = self.sprint_history[start..]
+            .iter()
+            .map(|record| SprintVelocity {
+                sprint_name: record.plan.sprint_name.clone(),
+                completed_points: record.points.completed_points,
+            })
+            .collect();
+        if sprints.is_empty() {
+            return None;
+        }
+
+        let total: u64 = sprints
+            .iter()
+            .map(|sprint| u64::from(sprint.completed_points))
+            .sum();
+        Some(VelocityReport {
+            window,
+            average_points: total as f64 / sprints.len() as f64,
+            sprints,
+        })
+    }
+
+    /// Starts a sprint, folding in any stories carried over from the last one
+    fn start_sprint(
+        &mut self,
+        thought_number: u32,
+        plan: &SprintPlan,
+        outcome: &mut TeamUpdateOutcome,
+    ) -> SprintPlan {
+        let mut plan = plan.clone();
+        for story_id in std::mem::take(&mut self.pending_carry_over) {
+            if !self.backlog.contains_key(&story_id) || plan.committed_story_ids.contains(&story_id)
+            {
+                continue;
+            }
+            plan.committed_story_ids.push(story_id.clone());
+            outcome.carried_in_story_ids.push(story_id);
+        }
+
+        for action in self.open_retro_actions() {
+            if !plan.retro_action_ids.contains(&action.id) {
+                plan.retro_action_ids.push(action.id.clone());
+            }
+        }
+
+        self.active_sprint = Some(ActiveSprint {
+            plan: plan.clone(),
+            started_at_thought: thought_number,
+            started_at: Utc::now(),
+            burndown: Vec::new(),
+        });
+        plan
+    }
+
+    /// Archives the active sprint, splitting its commitments into completed and incomplete
+    fn close_sprint(
+        &mut self,
+        thought_number: u32,
+        closure: &SprintClosure,
+    ) -> Option<SprintRecord> {
+        self.record_burndown(thought_number);
+        let active = self.active_sprint.take()?;
+        let points = self.sprint_points(&active.plan);
+
+        let (completed, incomplete): (Vec<String>, Vec<String>) = active
+            .plan
+            .committed_story_ids
+            .iter()
+            .cloned()
+            .partition(|story_id| {
+                self.backlog
+                    .get(story_id)
+                    .is_some_and(|story| story.status == StoryStatus::Done)
+            });
+        let carried_over = if closure.carry_over {
+            incomplete
+                .iter()
+                .filter(|story_id| self.backlog.contains_key(*story_id))
+                .cloned()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        self.pending_carry_over = carried_over.clone();
+
+        let record = SprintRecord {
+            plan: active.plan,
+            started_at_thought: active.started_at_thought,
+            closed_at_thought: thought_number,
+            started_at: active.started_at,
+            closed_at: Utc::now(),
+            completed_story_ids: completed,
+            incomplete_story_ids: incomplete,
+            carried_over_story_ids: carried_over,
+            points,
+            burndown: active.burndown,
+            notes: closure.notes.clone(),
+            retrospective: Vec::new(),
+        };
+        self.sprint_history.push(record.clone());
+        Some(record)
+    }
+
+    /// Archived sprints, optionally narrowed to a single sprint name
+    fn sprint_history(&self, sprint_name: Option<&str>) -> Vec<SprintRecord> {
+        self.sprint_history
+            .iter()
+            .filter(|record| sprint_name.is_none_or(|name| record.plan.sprint_name == name))
+            .cloned()
+            .collect()
+    }
+
+    /// Appends backlog changes to each story's audit trail
+    fn record_history(
+        &mut self,
+        thought_number: u32,
+        role: Option<&TeamRole>,
+        changes: &[BacklogChange],
+    ) {
+        let recorded_at = Utc::now();
+        for change in changes {
+            let status_changed = change.changes.iter().any(|field| field.field == "status");
+            let reason = match change.change_type {
+                BacklogChangeType::Removed => None,
+                BacklogChangeType::Added => change.item.status_reason.clone(),
+                BacklogChangeType::Updated if status_changed => change.item.status_reason.clone(),
+                BacklogChangeType::Updated => None,
+            };
+            self.story_history
+                .entry(change.item.id.clone())
+                .or_default()
+                .push(StoryHistoryEntry {
+                    thought_number,
+                    role: role.cloned(),
+                    change_type: change.change_type,
+                    changes: change.changes.clone(),
+                    reason,
+                    snapshot: change.item.clone(),
+                    recorded_at,
+                });
+        }
+    }
+
+    /// Returns everything recorded about a story, including after its removal
+    fn story_history(&self, story_id: &str) -> StoryHistoryResponse {
+        StoryHistoryResponse {
+            story_id: story_id.to_string(),
+            current: self.backlog.get(story_id).cloned(),
+            history: self
+                .story_history
+                .get(story_id)
+                .cloned()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Validates imported items like `backlogStories` and applies them, returning the
+    /// backlog changes and workflow warnings
+    fn import_backlog(
+        &mut self,
+        items: Vec<BacklogItem>,
+        mode: ImportMode,
+        thought_number: u32,
+        role: Option<&TeamRole>,
+    ) -> Result<TeamUpdateOutcome, McpError> {
+        let mut removed_ids: Vec<String> = match mode {
+            ImportMode::Merge => Vec::new(),
+            ImportMode::Replace => self
+                .backlog
+                .keys()
+                .filter(|id| !items.iter().any(|item| &item.id == *id))
+                .cloned()
+                .collect(),
+        };
+        removed_ids.sort();
+        if !items.is_empty() {
+            self.permissions().check(TeamAction::AddStories, role)?;
+        }
+        if !removed_ids.is_empty() {
+            self.permissions().check(TeamAction::RemoveStories, role)?;
+        }
+
+        let mut backlog = match mode {
+            ImportMode::Merge => self.backlog.clone(),
+            ImportMode::Replace => HashMap::new(),
+        };
+        for item in &items {
+            item.validate_fields("importbacklog")?;
+            backlog.insert(item.id.clone(), item.clone());
+        }
+        for item in &items {
+            self.validate_story_change(item, &backlog, &self.definition_of_done)?;
+        }
+        if let Some(cycle) = find_dependency_cycle(&backlog) {
+            return Err(create_validation_error(&format!(
+                "import would create a dependency cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        if let Some(cycle) = find_parent_cycle(&backlog) {
+            return Err(create_validation_error(&format!(
+                "import would create a parent cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let mut outcome = TeamUpdateOutcome::default();
+        for story_id in &removed_ids {
+            if let Some(removed) = self.backlog.remove(story_id) {
+                outcome
+                    .backlog_changes
+                    .push(BacklogChange::new(BacklogChangeType::Removed, removed));
+            }
+        }
+        for item in items {
+            self.upsert_story(item, &mut outcome);
+        }
+        self.record_history(thought_number, role, &outcome.backlog_changes);
+        self.record_burndown(thought_number);
+
+        Ok(outcome)
+    }
+
+    /// Inserts or replaces a story, recording what changed
+    fn upsert_story(&mut self, story: BacklogItem, outcome: &mut TeamUpdateOutcome) {
+        let Some(previous) = self.backlog.insert(story.id.clone(), story.clone()) else {
+            outcome
+                .backlog_changes
+                .push(BacklogChange::new(BacklogChangeType::Added, story));
+            return;
+        };
+
+        // Rejected transitions never get this far, so any violation here is a warning
+        if let Some(violation) = self.config.workflow.violation(&previous.status, &story) {
+            outcome.warnings.push(violation);
+        }
+        if story.status.progress() < previous.status.progress() {
+            outcome.regressions.push(StatusRegression {
+                story_id: story.id.clone(),
+                from: previous.status.clone(),
+                to: story.status.clone(),
+                reason: story.status_reason.clone(),
+            });
+        }
+        outcome
+            .backlog_changes
+            .push(BacklogChange::updated(&previous, story));
+    }
+
+    fn generate_report(
+        &self,
+        request: &DeliberateThinkingRequest,
+        outcome: &TeamUpdateOutcome,
+    ) -> ProjectManagerReport {
+        let mut bullets = Vec::new();
+        let backlog_snapshot = self.ordered_backlog();
+        let filtered_backlog = request
+            .backlog_filter
+            .as_ref()
+            .map(|filter| (filter, self.filtered_backlog(filter)));
+        if let Some((filter, items)) = &filtered_backlog {
+            if items.is_empty() {
+                bullets.push(format!(
+                    "Backlog filter: {}: no stories match filter",
+                    filter
+                ));
+            } else {
+                let ids = items
+                    .iter()
+                    .map(|item| item.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                bullets.push(format!(
+                    "Backlog filter: {} ({} of {} items): {}",
+                    filter,
+                    items.len(),
+                    self.backlog.len(),
+                    ids
+                ));
+            }
+        }
+        let pm_summary = outcome
+            .pm_summary
+            .clone()
+            .or_else(|| self.pm_summaries.last().cloned())
+            .or_else(|| {
+                if matches!(request.role, Some(TeamRole::ProjectManager)) {
+                    let summary = request.thought.trim();
+                    (!summary.is_empty()).then(|| summary.to_string())
+                } else {
+                    None
+                }
+            });
+
+        let pm_summary_text = pm_summary
+            .clone()
+            .unwrap_or_else(|| "No project manager summary provided yet".to_string());
+        bullets.push(format!("PM summary: {}", pm_summary_text));
+
+        if !outcome.new_discussion_points.is_empty() {
+            let discussion = outcome
+                .new_discussion_points
+                .iter()
+                .map(|point| format!("{}: {}", point.role, point.detail))
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Discussion points: {}", discussion));
+        } else if let Some(last_point) = self.discussion_log.last() {
+            bullets.push(format!(
+                "Discussion points: {}: {}",
+                last_point.role, last_point.detail
+            ));
+        } else {
+            bullets.push("Discussion points: none recorded yet".to_string());
+        }
+
+        if !outcome.new_decisions.is_empty() {
+            let decisions = outcome
+                .new_decisions
+                .iter()
+                .map(|record| match &record.supersedes {
+                    Some(previous) => format!(
+                        "{} {}: {} (supersedes {})",
+                        record.id, record.title, record.chosen_option, previous
+                    ),
+                    None => format!("{} {}: {}", record.id, record.title, record.chosen_option),
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Decisions: {}", decisions));
+        }
+
+        if !outcome.backlog_changes.is_empty() {
+            let changes = outcome
+                .backlog_changes
+                .iter()
+                .map(|change| change.summary())
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Backlog updates: {}", changes));
+        } else if backlog_snapshot.is_empty() {
+            bullets.push("Backlog updates: backlog is empty".to_string());
+        } else {
+            let highlights = backlog_snapshot
+                .iter()
+                .take(3)
+                .map(|item| format!("{} [{} | {}]", item.id, item.priority, item.status))
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Backlog focus: {}", highlights));
+        }
+
+        let value_scores = backlog_snapshot
+            .iter()
+            .filter_map(|item| {
+                item.value_score(self.prioritization)
+                    .map(|score| ValueScore {
+                        story_id: item.id.clone(),
+                        score,
+                    })
+            })
+            .collect::<Vec<_>>();
+        if !value_scores.is_empty() {
+            let top = value_scores
+                .iter()
+                .take(3)
+                .map(|entry| format!("{} {:.1}", entry.story_id, entry.score))
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Value scores ({}): {}", self.prioritization, top));
+        }
+
+        let backlog_tree = match request.backlog_view.unwrap_or_default() {
+            BacklogView::Flat => Vec::new(),
+            BacklogView::Grouped => self.grouped_backlog(),
+        };
+        let epics = backlog_snapshot
+            .iter()
+            .filter_map(|item| {
+                self.progress(&item.id)
+                    .map(|progress| format!("{} {}", item.id, progress))
+            })
+            .collect::<Vec<_>>();
+        if !epics.is_empty() {
+            bullets.push(format!("Epic progress: {}", epics.join("; ")));
+        }
+
+        if let Some(record) = &outcome.sprint_closed {
+            bullets.push(format!("Sprint closed: {}", record.summary()));
+        }
+
+        if !outcome.new_retro_actions.is_empty() {
+            let actions = outcome
+                .new_retro_actions
+                .iter()
+                .map(|action| action.to_string())
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Retrospective actions raised: {}", actions));
+        }
+
+        if let Some(plan) = outcome.sprint_plan_updated.as_ref().or(self.active_plan()) {
+            let stories = if plan.committed_story_ids.is_empty() {
+                "no stories committed".to_string()
+            } else {
+                plan.committed_story_ids.join(", ")
+            };
+            let participants = if plan.participants.is_empty() {
+                "no participants selected".to_string()
+            } else {
+                plan.participants
+                    .iter()
+                    .map(|participant| {
+                        let mut detail = participant.role.to_string();
+                        if let Some(reason) = &participant.reasoning {
+                            if !reason.trim().is_empty() {
+                                detail.push_str(&format!(" ({})", reason));
+                            }
+                        }
+                        if !participant.responsibilities.is_empty() {
+                            detail.push_str(&format!(
+                                " - {}",
+                                participant.responsibilities.join(", ")
+                            ));
+                        }
+                        detail
+                    })
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            };
+            let points = self.sprint_points(plan);
+            let capacity = points
+                .capacity_points
+                .map(|capacity| format!(" of {} capacity", capacity))
+                .unwrap_or_default();
+            bullets.push(format!(
+                "Sprint plan: {} goal '{}' lasting {} day(s); stories {}; participants {}; points {}/{} completed{}",
+                plan.sprint_name,
+                plan.goal,
+                plan.duration_days,
+                stories,
+                participants,
+                points.completed_points,
+                points.planned_points,
+                capacity
+            ));
+            if !outcome.carried_in_story_ids.is_empty() {
+                bullets.push(format!(
+                    "Carry-over: {} joined {} from the previous sprint",
+                    outcome.carried_in_story_ids.join(", "),
+                    plan.sprint_name
+                ));
+            }
+            let open_actions = self
+                .open_retro_actions()
+                .into_iter()
+                .filter(|action| plan.retro_action_ids.contains(&action.id))
+                .map(|action| action.to_string())
+                .collect::<Vec<_>>();
+            if !open_actions.is_empty() {
+                bullets.push(format!(
+                    "Retro actions for {}: {}",
+                    plan.sprint_name,
+                    open_actions.join("; ")
+                ));
+            }
+        } else if !self.pending_carry_over.is_empty() {
+            bullets.push(format!(
+                "Sprint plan: no active sprint; {} will carry over into the next one",
+                self.pending_carry_over.join(", ")
+            ));
+        } else {
+            bullets.push("Sprint plan: not yet defined".to_string());
+        }
+
+        let consensus = outcome
+            .consensus_state
+            .clone()
+            .unwrap_or_else(|| self.consensus.clone());
+        let blockers = if consensus.blockers.is_empty() {
+            "none".to_string()
+        } else {
+            consensus.blockers.join("; ")
+        };
+        let notes = consensus
+            .notes
+            .as_deref()
+            .filter(|note| !note.trim().is_empty())
+            .unwrap_or("no additional notes");
+        let waiting_on_user = self.waiting_on_user();
+        let votes = if consensus.votes.is_empty() {
+            "none".to_string()
+        } else {
+            consensus
+                .votes
+                .iter()
+                .map(|vote| format!("{} {}", vote.role, bool_to_yes(vote.ready)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        bullets.push(format!(
+            "Consensus: ready_for_code_change={} ({}; votes {}; holding up {}) blockers {} notes {} waiting_on_user={}",
+            bool_to_yes(consensus.ready_for_code_changes),
+            consensus.quorum_rule,
+            votes,
+            consensus.holding_up_summary(),
+            blockers,
+            notes,
+            bool_to_yes(waiting_on_user)
+        ));
+
+        // process_request samples the burndown before reports are generated
+        let burndown = self.burndown();
+        if let Some(burndown) = &burndown {
+            bullets.push(burndown.ascii_chart(self.config.burndown_granularity));
+        }
+        let velocity = self.velocity();
+        if let Some(velocity) = &velocity {
+            bullets.push(velocity.ascii_chart());
+        }
+
+        if !outcome.elicited_answers.is_empty() {
+            let answers = outcome
+                .elicited_answers
+                .iter()
+                .map(|question| {
+                    format!(
+                        "{} {} -> {}",
+                        question.id,
+                        question.question,
+                        question.answer.as_deref().unwrap_or_default()
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("User answers: {}", answers));
+        }
+
+        let open_questions: Vec<UserQuestion> =
+            self.open_questions().into_iter().cloned().collect();
+        if !open_questions.is_empty() {
+            let questions = open_questions
+                .iter()
+                .map(|question| question.to_string())
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Questions for the user: {}", questions));
+        }
+
+        let blocker_resolution =
+            (!self.blockers.is_empty()).then(|| self.blocker_resolution(request.thought_number));
+        if let Some(resolution) = &blocker_resolution {
+            bullets.push(format!("Blockers: {}", resolution));
+        }
+
+        let top_risks: Vec<Risk> = self
+            .risks_by_exposure()
+            .into_iter()
+            .filter(|risk| risk.is_active())
+            .take(3)
+            .cloned()
+            .collect();
+        if !top_risks.is_empty() {
+            let risks = top_risks
+                .iter()
+                .map(|risk| risk.to_string())
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Top risks: {}", risks));
+        }
+
+        if !outcome.regressions.is_empty() {
+            let regressions = outcome
+                .regressions
+                .iter()
+                .map(|regression| regression.to_string())
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Regressions: {}", regressions));
+        }
+
+        let work_by_type = self.work_by_type();
+        if !work_by_type.is_empty() {
+            let breakdown = work_by_type
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "{} {} ({} done, {} points)",
+                        entry.item_type, entry.total_items, entry.done_items, entry.total_points
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("; ");
+            bullets.push(format!("Work by type: {}", breakdown));
+        }
+
+        let mut warnings = outcome.warnings.clone();
+        warnings.extend(self.dependency_warnings());
+        warnings.extend(self.capacity_warnings());
+        warnings.extend(self.timebox_warnings(request.thought_number));
+        if !warnings.is_empty() {
+            bullets.push(format!("Warnings: {}", warnings.join("; ")));
+        }
+
+        ProjectManagerReport {
+            bullets,
+            pm_summary,
+            new_discussion_points: outcome.new_discussion_points.clone(),
+            backlog_snapshot,
+            filtered_backlog: filtered_backlog.map(|(_, items)| items),
+            backlog_tree,
+            active_sprint: self.active_plan().cloned(),
+            sprint_closed: outcome.sprint_closed.clone(),
+            pending_carry_over: self.pending_carry_over.clone(),
+            open_retro_actions: self.open_retro_actions().into_iter().cloned().collect(),
+            new_decisions: outcome.new_decisions.clone(),
+            open_questions,
+            blocker_resolution,
+            top_risks,
+            burndown,
+            velocity,
+            consensus,
+            waiting_on_user,
+            warnings,
+            work_by_type,
+            prioritization: self.prioritization,
+            value_scores,
+            sprint_points: self.active_sprint_points(),
+            definition_of_done: self.definition_of_done.clone(),
+            regressions: outcome.regressions.clone(),
+        }
+    }
+
+    /// Items matching the filter, in `ordered_backlog` order
+    fn filtered_backlog(&self, filter: &BacklogFilter) -> Vec<BacklogItem> {
+        let sprint_story_ids = self
+            .active_plan()
+            .map(|plan| plan.committed_story_ids.as_slice())
+            .unwrap_or_default();
+        self.ordered_backlog()
+            .into_iter()
+            .filter(|item| filter.matches(item, sprint_story_ids))
+            .collect()
+    }
+
+    /// Backlog in dependency order, using priority and status to break ties
+    fn ordered_backlog(&self) -> Vec<BacklogItem> {
+        let scheme = self.prioritization;
+        let mut remaining: Vec<&BacklogItem> = self.backlog.values().collect();
+        remaining.sort_by(|a, b| {
+            // Scored items rank ahead of unscored ones; priority level breaks ties.
+            let by_score = match (a.value_score(scheme), b.value_score(scheme)) {
+                (Some(a_score), Some(b_score)) => b_score.total_cmp(&a_score),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            };
+            by_score
+                .then_with(|| a.priority.rank().cmp(&b.priority.rank()))
+                .then_with(|| a.status.rank().cmp(&b.status.rank()))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        let mut placed: HashSet<&str> = HashSet::new();
+        let mut items = Vec::with_capacity(remaining.len());
+        while !remaining.is_empty() {
+            // Dependencies on unknown stories do not hold anything back; a cycle
+            // (only possible in hand-edited state) falls back to priority order.
+            let next = remaining
+                .iter()
+                .position(|item| {
+                    item.depends_on.iter().all(|dependency| {
+                        placed.contains(dependency.as_str())
+                            || !self.backlog.contains_key(dependency)
+                    })
+                })
+                .unwrap_or(0);
+            let item = remaining.remove(next);
+            placed.insert(item.id.as_str());
+            items.push(item.clone());
+        }
+        items
+    }
+
+    /// Committed and completed story points for the active sprint, if there is one
+    fn active_sprint_points(&self) -> Option<SprintPoints> {
+        self.active_plan().map(|plan| self.sprint_points(plan))
+    }
+
+    /// Totals the committed and completed story points for a sprint plan
+    fn sprint_points(&self, plan: &SprintPlan) -> SprintPoints {
+        let mut points = SprintPoints {
+            sprint_name: plan.sprint_name.clone(),
+            planned_points: 0,
+            completed_points: 0,
+            capacity_points: None,
+            unestimated_story_ids: Vec::new(),
+        };
+
+        for story_id in &plan.committed_story_ids {
+            let Some(story) = self.backlog.get(story_id) else {
+                continue;
+            };
+            match story.story_points {
+                // Estimates are unbounded, so totals saturate rather than overflow
+                Some(estimate) => {
+                    points.planned_points = points.planned_points.saturating_add(estimate);
+                    if story.status == StoryStatus::Done {
+                        points.completed_points = points.completed_points.saturating_add(estimate);
+                    }
+                }
+                None => points.unestimated_story_ids.push(story_id.clone()),
+            }
+        }
+
+        points.capacity_points = plan
+            .participants
+            .iter()
+            .filter_map(|participant| participant.capacity_points)
+            .reduce(u32::saturating_add);
+        points
+    }
+
+    /// Warns when committed work exceeds the declared sprint capacity
+    fn capacity_warnings(&self) -> Vec<String> {
+        let Some(plan) = self.active_plan() else {
+            return Vec::new();
+        };
+
+        let points = self.sprint_points(plan);
+        let mut warnings = Vec::new();
+        if let Some(capacity) = points.capacity_points {
+            if points.planned_points > capacity {
+                warnings.push(format!(
+                    "{} commits {} story points but capacity is {}",
+                    plan.sprint_name, points.planned_points, capacity
+                ));
+            }
+        }
+        if !points.unestimated_story_ids.is_empty() {
+            warnings.push(format!(
+                "{} has unestimated commitments: {}",
+                plan.sprint_name,
+                points.unestimated_story_ids.join(", ")
+            ));
+        }
+        warnings
+    }
+
+    /// Backlog nested under its epics, each level in `ordered_backlog` order
+    fn grouped_backlog(&self) -> Vec<BacklogNode> {
+        let ordered = self.ordered_backlog();
+        ordered
+            .iter()
+            .filter(|item| {
+                item.parent_id
+                    .as_ref()
+                    .is_none_or(|parent| !self.backlog.contains_key(parent))
+            })
+            .map(|item| self.backlog_node(item, &ordered))
+            .collect()
+    }
+
+    fn backlog_node(&self, item: &BacklogItem, ordered: &[BacklogItem]) -> BacklogNode {
+        let children = ordered
+            .iter()
+            .filter(|child| child.parent_id.as_ref() == Some(&item.id))
+            .map(|child| self.backlog_node(child, ordered))
+            .collect();
+        BacklogNode {
+            item: item.clone(),
+            progress: self.progress(&item.id),
+            children,
+        }
+    }
+
+    fn descendants(&self, story_id: &str) -> Vec<&BacklogItem> {
+        let mut found = Vec::new();
+        let mut pending = vec![story_id];
+        while let Some(parent) = pending.pop() {
+            for item in self.backlog.values() {
+                if item.parent_id.as_deref() == Some(parent) {
+                    found.push(item);
+                    pending.push(&item.id);
+                }
+            }
+        }
+        found
+    }
+
+    /// Rolls up status and points from all of an item's descendants
+    fn progress(&self, story_id: &str) -> Option<BacklogProgress> {
+        let descendants = self.descendants(story_id);
+        if descendants.is_empty() {
+            return None;
+        }
+
+        let mut progress = BacklogProgress {
+            total_items: descendants.len() as u32,
+            done_items: 0,
+            total_points: 0,
+            done_points: 0,
+            percent_complete: 0,
+        };
+        for item in descendants {
+            let done = item.status == StoryStatus::Done;
+            let points = item.story_points.unwrap_or(0);
+            progress.total_points = progress.total_points.saturating_add(points);
+            if done {
+                progress.done_items += 1;
+                progress.done_points = progress.done_points.saturating_add(points);
+            }
+        }
+        // Done is a share of the total, so the percentage fits in u32 once computed in u64
+        progress.percent_complete = (u64::from(progress.done_points) * 100)
+            .checked_div(u64::from(progress.total_points))
+            .unwrap_or(u64::from(progress.done_items) * 100 / u64::from(progress.total_items))
+            as u32;
+        Some(progress)
+    }
+
+    /// Item counts and points per item type, skipping types with no items
+    fn work_by_type(&self) -> Vec<TypeBreakdown> {
+        ItemType::all()
+            .into_iter()
+            .filter_map(|item_type| {
+                let items: Vec<&BacklogItem> = self
+                    .backlog
+                    .values()
+                    .filter(|item| item.item_type == item_type)
+                    .collect();
+                (!items.is_empty()).then(|| TypeBreakdown {
+                    item_type,
+                    total_items: items.len() as u32,
+                    done_items: items
+                        .iter()
+                        .filter(|item| item.status == StoryStatus::Done)
+                        .count() as u32,
+                    total_points: items
+                        .iter()
+                        .filter_map(|item| item.story_points)
+                        .fold(0, u32::saturating_add),
+                })
+            })
+            .collect()
+    }
+
+    /// Thought in which the story last moved to In Progress, from its audit trail
+    fn in_progress_since(&self, story_id: &str) -> Option<u32> {
+        self.story_history
+            .get(story_id)?
+            .iter()
+            .rev()
+            .find(|entry| {
+                let moved = matches!(entry.change_type, BacklogChangeType::Added)
+                    || entry.changes.iter().any(|change| change.field == "status");
+                moved && entry.snapshot.status == StoryStatus::InProgress
+            })
+            .map(|entry| entry.thought_number)
+    }
+
+    /// Warns about spikes still In Progress past their timebox
+    fn timebox_warnings(&self, thought_number: u32) -> Vec<String> {
+        let mut spikes: Vec<&BacklogItem> = self
+            .backlog
+            .values()
+            .filter(|item| item.item_type == ItemType::Spike)
+            .filter(|item| item.status == StoryStatus::InProgress)
+            .collect();
+        spikes.sort_by(|a, b| a.id.cmp(&b.id));
+
+        spikes
+            .into_iter()
+            .filter_map(|spike| {
+                let timebox = spike.timebox_thoughts?;
+                let started = self.in_progress_since(&spike.id)?;
+                let elapsed = thought_number.saturating_sub(started);
+                (elapsed > timebox).then(|| {
+                    format!(
+                        "Spike {} has run {} thought(s) past its {}-thought timebox (started at thought {})",
+                        spike.id,
+                        elapsed - timebox,
+                        timebox,
+                        started
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Flags sprint commitments whose dependencies are neither committed nor Done
+    fn dependency_warnings(&self) -> Vec<String> {
+        let Some(plan) = self.active_plan() else {
+            return Vec::new();
+        };
+
+        let mut warnings = Vec::new();
+        for story_id in &plan.committed_story_ids {
+            let Some(story) = self.backlog.get(story_id) else {
+                continue;
+            };
+            for dependency in &story.depends_on {
+                let committed = plan.committed_story_ids.contains(dependency);
+                let done = self
+                    .backlog
+                    .get(dependency)
+                    .is_some_and(|item| item.status == StoryStatus::Done);
+                if !committed && !done {
+                    warnings.push(format!(
+                        "{} depends on {}, which is neither committed to {} nor Done",
+                        story_id, dependency, plan.sprint_name
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+}
+
+/// Finds a dependency cycle in the backlog, returning the story ids along it
+fn find_dependency_cycle(backlog: &HashMap<String, BacklogItem>) -> Option<Vec<String>> {
+    fn visit<'a>(
+        id: &'a str,
+        backlog: &'a HashMap<String, BacklogItem>,
+        path: &mut Vec<&'a str>,
+        finished: &mut HashSet<&'a str>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|entry| *entry == id) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|s| s.to_string()).collect();
+            cycle.push(id.to_string());
+            return Some(cycle);
+        }
+        if finished.contains(id) {
+            return None;
+        }
+
+        path.push(id);
+        if let Some(item) = backlog.get(id) {
+            for dependency in &item.depends_on {
+                if let Some(cycle) = visit(dependency, backlog, path, finished) {
+                    return Some(cycle);
+                }
+            }
+        }
+        path.pop();
+        finished.insert(id);
+        None
+    }
+
+    let mut ids: Vec<&String> = backlog.keys().collect();
+    ids.sort();
+    let mut finished = HashSet::new();
+    ids.into_iter()
+        .find_map(|id| visit(id, backlog, &mut Vec::new(), &mut finished))
+}
+
+fn bool_to_yes(value: bool) -> &'static str {
+    if value {
+        "yes"
+    } else {
+        "no"
+    }
+}
+
+/// Deliberate thinking server state
+#[derive(Debug, Default)]
+pub struct DeliberateThinkingState {
+    pub thought_history: Vec<ThoughtData>,
+    pub branches: HashMap<String, Vec<ThoughtData>>,
+    pub current_branch: Option<String>,
+    pub team: TeamState,
+    /// Minimum level set by the client through logging/setLevel (info when unset)
+    pub log_level: Option<LoggingLevel>,
+}
+
+impl DeliberateThinkingState {
+    /// Gets the current thought history (from branch or main)
+    fn get_current_history(&self) -> &[ThoughtData] {
+        match &self.current_branch {
+            Some(branch_id) => self
+                .branches
+                .get(branch_id)
+                .map(|v| v.as_slice())
+                .unwrap_or(&self.thought_history),
+            None => &self.thought_history,
+        }
+    }
+
+    /// Gets the current thought history length
+    fn get_history_length(&self) -> u32 {
+        self.get_current_history().len() as u32
+    }
+
+    /// Handles branching logic
+    fn handle_branching(&mut self, branch_from: u32, branch_id: String, thought_data: ThoughtData) {
+        // Create branch if it doesn't exist
+        if !self.branches.contains_key(&branch_id) {
+            let branch_base: Vec<ThoughtData> = self
+                .thought_history
+                .iter()
+                .take_while(|t| t.thought_number <= branch_from)
+                .cloned()
+                .collect();
+            self.branches.insert(branch_id.clone(), branch_base);
+        }
+
+        // Add thought to the branch
+        if let Some(branch) = self.branches.get_mut(&branch_id) {
+            branch.push(thought_data);
+        }
+
+        self.current_branch = Some(branch_id);
+    }
+
+    /// Handles revision of existing thoughts
+    fn handle_revision(&mut self, revises: u32, thought_data: ThoughtData) {
+        match &self.current_branch {
+            Some(branch_id) => {
+                if let Some(branch) = self.branches.get_mut(branch_id) {
+                    Self::revise_or_append(branch, revises, thought_data);
+                }
+            }
+            None => {
+                Self::revise_or_append(&mut self.thought_history, revises, thought_data);
+            }
+        }
+    }
+
+    /// Helper to revise a thought in a list or append if not found
+    fn revise_or_append(thoughts: &mut Vec<ThoughtData>, revises: u32, thought_data: ThoughtData) {
+        if let Some(thought) = thoughts.iter_mut().find(|t| t.thought_number == revises) {
+            *thought = thought_data;
+        } else {
+            thoughts.push(thought_data);
+        }
+    }
+
+    /// Adds a regular thought to the current context
+    fn add_thought(&mut self, thought_data: ThoughtData) {
+        match &self.current_branch {
+            Some(branch_id) => {
+                if let Some(branch) = self.branches.get_mut(branch_id) {
+                    branch.push(thought_data);
+                }
+            }
+            None => {
+                self.thought_history.push(thought_data);
+            }
+        }
+    }
+
+    /// Gets all branch names
+    fn get_branch_names(&self) -> Vec<String> {
+        self.branches.keys().cloned().collect()
+    }
+}
+
+/// Deliberate thinking server implementation
+#[derive(Clone)]
+pub struct DeliberateThinkingServer {
+    state: Arc<Mutex<DeliberateThinkingState>>,
+    tool_router: ToolRouter<Self>,
+    prompt_router: PromptRouter<Self>,
+}
+
+impl DeliberateThinkingServer {
+    pub fn new() -> Self {
+        Self::with_config(TeamConfig::default())
+    }
+
+    pub fn with_config(config: TeamConfig) -> Self {
+        let state = DeliberateThinkingState {
+            team: TeamState::with_config(config),
+            ..Default::default()
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            tool_router: Self::tool_router(),
+            prompt_router: Self::prompt_router(),
+        }
+    }
+}
+
+impl Default for DeliberateThinkingServer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Helper function to validate minimum values
+fn validate_min_value(field_name: &str, value: u32, min: u32) -> Result<(), McpError> {
+    if value < min {
+        Err(create_validation_error(&format!(
+            "{} must be at least {}",
+            field_name, min
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_non_empty(field_name: &str, value: &str) -> Result<(), McpError> {
+    if value.trim().is_empty() {
+        Err(create_validation_error(&format!(
+            "{} cannot be empty",
+            field_name
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Helper function to create validation errors
+fn create_validation_error(message: &str) -> McpError {
+    McpError {
+        code: ErrorCode(-32602),
+        message: message.to_string().into(),
+        data: None,
+    }
+}
+
+/// Helper function to create serialization errors
+fn create_serialization_error(error: impl std::fmt::Display) -> McpError {
+    McpError {
+        code: ErrorCode(-32603),
+        message: format!("Failed to serialize response: {}", error).into(),
+        data: None,
+    }
+}
+
+#[tool_router]
+impl DeliberateThinkingServer {
+    /// Deliberate thinking tool for dynamic and reflective problem-solving
+    #[tool(
+        name = "deliberatethinking",
+        description = "A detailed tool for dynamic and reflective problem-solving through thoughts.
+This tool helps analyze problems through a flexible thinking process that can adapt and evolve.
+Each thought can build on, question, or revise previous insights as understanding deepens.
+
+When to use this tool:
+- Breaking down complex problems into steps
+- Planning and design with room for revision
+- Analysis that might need course correction
+- Problems where the full scope might not be clear initially
+- Problems that require a multi-step solution
+- Tasks that need to maintain context over multiple steps
+- Situations where irrelevant information needs to be filtered out
+
+Key features:
+- You can adjust total_thoughts up or down as you progress
+- You can question or revise previous thoughts
+- You can add more thoughts even after reaching what seemed like the end
+- You can express uncertainty and explore alternative approaches
+- Not every thought needs to build linearly - you can branch or backtrack
+- Generates a solution hypothesis
+- Verifies the hypothesis based on the Chain of Thought steps
+- Repeats the process until satisfied
+- Provides a correct answer"
+    )]
+
+    pub async fn deliberate_thinking(
+        &self,
+        Parameters(request): Parameters<DeliberateThinkingRequest>,
+        peer: Peer<RoleServer>,
+        meta: Meta,
+    ) -> Result<CallToolResult, McpError> {
+        let mut state = self.state.lock().await;
+
+        // Validate parameters
+        request.validate(state.team.permissions())?;
+        state.team.validate_request(&request)?;
+
+        // Convert request to thought data (consumes the request)
+        let thought_data = ThoughtData::from(request.clone());
+
+        // Update team collaboration state
+        let mut team_outcome = state.team.process_request(&request);
+
+        // Process the thought based on its type
+        match (
+            &request.branch_from_thought,
+            &request.branch_id,
+            &request.revises_thought,
+        ) {
+            // Branching case
+            (Some(branch_from), Some(branch_id), _) => {
+                state.handle_branching(*branch_from, branch_id.clone(), thought_data);
+            }
+            // Revision case
+            (_, _, Some(revises)) => {
+                state.handle_revision(*revises, thought_data);
+            }
+            // Regular thought case
+            _ => {
+                state.add_thought(thought_data);
+            }
         }
 
-        let consensus = outcome
-            .consensus_state
-            .clone()
-            .unwrap_or_else(|| self.consensus.clone());
-        let blockers = if consensus.blockers.is_empty() {
-            "none".to_string()
-        } else {
-            consensus.blockers.join("; ")
-        };
-        let notes = consensus
-            .notes
-            .as_deref()
-            .filter(|note| !note.trim().is_empty())
-            .unwrap_or("no additional notes");
-        let waiting_on_user = outcome
-            .awaiting_user_input
-            .unwrap_or(self.awaiting_user_input);
-        bullets.push(format!(
-            "Consensus: ready_for_code_change={} blockers {} notes {} waiting_on_user={}",
-            bool_to_yes(consensus.ready_for_code_changes),
-            blockers,
-            notes,
-            bool_to_yes(waiting_on_user)
-        ));
+        // Ask the user directly when the client supports elicitation; otherwise the
+        // thought is queued and the report's waitingOnUser flag tells the assistant to ask
+        let elicitation = state
+            .team
+            .request_user_input(&request, peer.supports_elicitation());
+        if let Some(elicitation) = elicitation {
+            drop(state);
+            let result = peer.create_elicitation(elicitation.clone()).await;
+            state = self.state.lock().await;
+            match result {
+                Ok(CreateElicitationResult {
+                    action: ElicitationAction::Accept,
+                    content: Some(content),
+                }) => {
+                    state.team.record_elicited_answers(
+                        &request,
+                        &elicitation,
+                        &content,
+                        &mut team_outcome,
+                    );
+                }
+                // Declined or cancelled: nothing new is queued and open questions stay open
+                Ok(_) => {}
+                Err(error) => {
+                    log::warn!(
+                        "Elicitation failed, queueing the question instead: {}",
+                        error
+                    );
+                    state.team.queue_thought_question(&request);
+                }
+            }
+        }
 
-        ProjectManagerReport {
-            bullets,
-            pm_summary,
-            new_discussion_points: outcome.new_discussion_points.clone(),
-            backlog_snapshot,
-            active_sprint: self.active_sprint.clone(),
-            consensus,
-            waiting_on_user,
+        // Build the project manager report summarising this step
+        let pm_report = state.team.generate_report(&request, &team_outcome);
+
+        // Hosts that sent a progress token get a "thinking n/total" indicator; sprint
+        // completion rides along in the message so the token's progress only moves forward
+        let progress = meta
+            .get_progress_token()
+            .map(|token| thought_progress(token, &request, pm_report.sprint_points.as_ref()));
+
+        // Create response
+        let response = DeliberateThinkingResponse::new(
+            &request,
+            state.get_branch_names(),
+            state.get_history_length(),
+            pm_report,
+        );
+
+        let stderr_logging = state.team.config.stderr_logging;
+        let notify_client = logging_enabled(state.log_level, LoggingLevel::Info);
+        drop(state);
+
+        if let Some(progress) = progress {
+            if let Err(error) = peer.notify_progress(progress).await {
+                log::warn!("Failed to send progress notification: {}", error);
+            }
+        }
+
+        // Log the thought to the client, and to stderr when configured
+        for line in thought_log_lines(&request) {
+            if stderr_logging {
+                log::info!("{}", line);
+            }
+            if notify_client {
+                let message = LoggingMessageNotificationParam {
+                    level: LoggingLevel::Info,
+                    logger: Some(LOGGER_NAME.to_string()),
+                    data: serde_json::Value::String(line),
+                };
+                if let Err(error) = peer.notify_logging_message(message).await {
+                    log::warn!("Failed to send log notification: {}", error);
+                }
+            }
         }
+
+        // Serialize response
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
     }
 
-    fn ordered_backlog(&self) -> Vec<BacklogItem> {
-        let mut items: Vec<BacklogItem> = self.backlog.values().cloned().collect();
-        items.sort_by(|a, b| {
-            a.priority
-                .rank()
-                .cmp(&b.priority.rank())
-                .then_with(|| a.status.rank().cmp(&b.status.rank()))
-                .then_with(|| a.id.cmp(&b.id))
+    /// Sprint history lookup
+    #[tool(
+        name = "sprinthistory",
+        description = "Returns closed sprints in the order they were closed, each with its plan, the thoughts it started and closed in, completed and incomplete commitments, carried-over stories and planned versus completed points. Pass sprintName to look up a single sprint."
+    )]
+    pub async fn sprint_history(
+        &self,
+        Parameters(request): Parameters<SprintHistoryRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let state = self.state.lock().await;
+        let response = SprintHistoryResponse {
+            sprints: state.team.sprint_history(request.sprint_name.as_deref()),
+        };
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
+    }
+
+    /// Backlog query
+    #[tool(
+        name = "querybacklog",
+        description = "Filters the backlog by labels, owner role, status, priority, item type, free text (id, title and notes) and active sprint membership. Every supplied criterion must match; list criteria match any of their values except labels, which must all be present. Results come back in dependency and priority order."
+    )]
+    pub async fn query_backlog(
+        &self,
+        Parameters(filter): Parameters<BacklogFilter>,
+    ) -> Result<CallToolResult, McpError> {
+        let state = self.state.lock().await;
+        let items = state.team.filtered_backlog(&filter);
+        let response = BacklogQueryResponse {
+            filter: filter.to_string(),
+            total: items.len(),
+            items,
+        };
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
+    }
+
+    /// Backlog import
+    #[tool(
+        name = "importbacklog",
+        description = "Imports backlog items from CSV (header row required; map backlog fields to headers with columns), a JSON array of backlog items, or a Markdown checklist ('- [ ] S-1: title', '- [x]' marks Done, indented items nest under the item above). Items without an id get an IMP-n id. Merge mode adds new items and overwrites matching ids; replace mode also removes everything not imported. Repeated ids are reported and only the first is kept. Set dryRun to preview the changes."
+    )]
+    pub async fn import_backlog(
+        &self,
+        Parameters(request): Parameters<BacklogImportRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut state = self.state.lock().await;
+        let (items, duplicate_ids) = request.parse(&state.team.backlog)?;
+        let imported = items.len();
+        let thought_number = request.thought_number.unwrap_or_else(|| {
+            state
+                .get_current_history()
+                .last()
+                .map(|thought| thought.thought_number)
+                .unwrap_or(0)
         });
-        items
+
+        let role = request.role.as_ref();
+        let outcome = if request.dry_run {
+            let mut preview = state.team.clone();
+            preview.import_backlog(items, request.mode, thought_number, role)?
+        } else {
+            state
+                .team
+                .import_backlog(items, request.mode, thought_number, role)?
+        };
+
+        let changes = &outcome.backlog_changes;
+        let ids = |wanted: BacklogChangeType| {
+            changes
+                .iter()
+                .filter(|change| change.change_type == wanted)
+                .map(|change| change.item.id.clone())
+                .collect::<Vec<_>>()
+        };
+        let response = BacklogImportResponse {
+            dry_run: request.dry_run,
+            mode: request.mode,
+            imported,
+            added: ids(BacklogChangeType::Added),
+            updated: ids(BacklogChangeType::Updated),
+            removed: ids(BacklogChangeType::Removed),
+            duplicate_ids,
+            changes: changes.iter().map(|change| change.summary()).collect(),
+            warnings: outcome.warnings.clone(),
+        };
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
     }
-}
 
-fn bool_to_yes(value: bool) -> &'static str {
-    if value {
-        "yes"
-    } else {
-        "no"
+    /// Backlog export
+    #[tool(
+        name = "exportbacklog",
+        description = "Exports the backlog in dependency and priority order, optionally narrowed with a backlog filter. csv produces the columns importbacklog reads; githubIssues produces a JSON array of {title, body, labels, assigneeRole} issues, with notes, acceptance criteria and dependencies in the body and priority, status and type labels, ready for a local bulk import script. Nothing is sent over the network."
+    )]
+    pub async fn export_backlog(
+        &self,
+        Parameters(request): Parameters<BacklogExportRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let state = self.state.lock().await;
+        let items = match &request.filter {
+            Some(filter) => state.team.filtered_backlog(filter),
+            None => state.team.ordered_backlog(),
+        };
+        let content = match request.format {
+            ExportFormat::Csv => export_csv(&items),
+            ExportFormat::GithubIssues => {
+                let issues: Vec<GithubIssue> = items.iter().map(GithubIssue::from).collect();
+                serde_json::to_string_pretty(&issues).map_err(create_serialization_error)?
+            }
+        };
+        let response = BacklogExportResponse {
+            format: request.format,
+            items: items.len(),
+            content,
+        };
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
     }
-}
 
-/// Deliberate thinking server state
-#[derive(Debug, Default)]
-pub struct DeliberateThinkingState {
-    pub thought_history: Vec<ThoughtData>,
-    pub branches: HashMap<String, Vec<ThoughtData>>,
-    pub current_branch: Option<String>,
-    pub team: TeamState,
-}
+    /// Decision log lookup and ADR export
+    #[tool(
+        name = "decisionlog",
+        description = "Returns the team's decision records (title, context, options considered, chosen option, consequences, deciding roles, originating thought, and supersession links). Set format to adrMarkdown to also receive one numbered ADR Markdown file per decision, e.g. 0001-use-postgres.md, ready to save under docs/adr."
+    )]
+    pub async fn decision_log(
+        &self,
+        Parameters(request): Parameters<DecisionLogRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let state = self.state.lock().await;
+        let response = state.team.decision_log(&request);
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
 
-impl DeliberateThinkingState {
-    /// Gets the current thought history (from branch or main)
-    fn get_current_history(&self) -> &[ThoughtData] {
-        match &self.current_branch {
-            Some(branch_id) => self
-                .branches
-                .get(branch_id)
-                .map(|v| v.as_slice())
-                .unwrap_or(&self.thought_history),
-            None => &self.thought_history,
-        }
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
     }
 
-    /// Gets the current thought history length
-    fn get_history_length(&self) -> u32 {
-        self.get_current_history().len() as u32
+    /// Records the user's answer to a queued question
+    #[tool(
+        name = "answerquestion",
+        description = "Records the user's answer to a question the team queued with the questions field, e.g. {\"questionId\": \"Q-1\", \"answer\": \"Use Postgres\"}. The answer is added to the discussion log. Returns whether the team is still waiting on the user and which questions remain open."
+    )]
+    pub async fn answer_question(
+        &self,
+        Parameters(request): Parameters<AnswerQuestionRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let mut state = self.state.lock().await;
+        let response = state.team.answer_question(&request)?;
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
     }
 
-    /// Handles branching logic
-    fn handle_branching(&mut self, branch_from: u32, branch_id: String, thought_data: ThoughtData) {
-        // Create branch if it doesn't exist
-        if !self.branches.contains_key(&branch_id) {
-            let branch_base: Vec<ThoughtData> = self
-                .thought_history
+    /// Blocker log lookup
+    #[tool(
+        name = "blockerlog",
+        description = "Returns every blocker with its owner, linked stories, raised, reopened and resolved thoughts and resolution, plus resolution-time statistics measured in thoughts. Filter by status (open or resolved)."
+    )]
+    pub async fn blocker_log(
+        &self,
+        Parameters(request): Parameters<BlockerLogRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let state = self.state.lock().await;
+        let latest_thought = state
+            .thought_history
+            .last()
+            .map(|thought| thought.thought_number)
+            .unwrap_or(0);
+        let response = BlockerLogResponse {
+            blockers: state
+                .team
+                .blockers
                 .iter()
-                .take_while(|t| t.thought_number <= branch_from)
+                .filter(|blocker| request.status.is_none_or(|status| blocker.status == status))
                 .cloned()
-                .collect();
-            self.branches.insert(branch_id.clone(), branch_base);
-        }
-
-        // Add thought to the branch
-        if let Some(branch) = self.branches.get_mut(&branch_id) {
-            branch.push(thought_data);
-        }
+                .collect(),
+            resolution: state.team.blocker_resolution(latest_thought),
+        };
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
 
-        self.current_branch = Some(branch_id);
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
     }
 
-    /// Handles revision of existing thoughts
-    fn handle_revision(&mut self, revises: u32, thought_data: ThoughtData) {
-        match &self.current_branch {
-            Some(branch_id) => {
-                if let Some(branch) = self.branches.get_mut(branch_id) {
-                    Self::revise_or_append(branch, revises, thought_data);
-                }
-            }
-            None => {
-                Self::revise_or_append(&mut self.thought_history, revises, thought_data);
-            }
-        }
-    }
+    /// Risk register lookup
+    #[tool(
+        name = "riskregister",
+        description = "Returns the team's risk register, highest exposure (likelihood x impact) first. Each risk carries its mitigation, owner role, status and linked stories. Filter by status (open, mitigating, accepted, closed) to narrow the list."
+    )]
+    pub async fn risk_register(
+        &self,
+        Parameters(request): Parameters<RiskRegisterRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        let state = self.state.lock().await;
+        let risks = state
+            .team
+            .risks_by_exposure()
+            .into_iter()
+            .filter(|risk| request.status.is_empty() || request.status.contains(&risk.status))
+            .cloned()
+            .collect();
+        let response = RiskRegisterResponse { risks };
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
 
-    /// Helper to revise a thought in a list or append if not found
-    fn revise_or_append(thoughts: &mut Vec<ThoughtData>, revises: u32, thought_data: ThoughtData) {
-        if let Some(thought) = thoughts.iter_mut().find(|t| t.thought_number == revises) {
-            *thought = thought_data;
-        } else {
-            thoughts.push(thought_data);
-        }
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
     }
 
-    /// Adds a regular thought to the current context
-    fn add_thought(&mut self, thought_data: ThoughtData) {
-        match &self.current_branch {
-            Some(branch_id) => {
-                if let Some(branch) = self.branches.get_mut(branch_id) {
-                    branch.push(thought_data);
-                }
-            }
-            None => {
-                self.thought_history.push(thought_data);
-            }
+    /// Story audit trail lookup
+    #[tool(
+        name = "storyhistory",
+        description = "Returns the full change history of a backlog story: every add, update and removal with the thought number, team role, changed fields (old and new values), status reason and time. Use it to answer questions such as who moved a story to Blocked and why."
+    )]
+    pub async fn story_history(
+        &self,
+        Parameters(request): Parameters<StoryHistoryRequest>,
+    ) -> Result<CallToolResult, McpError> {
+        validate_non_empty("storyId", &request.story_id)?;
+
+        let state = self.state.lock().await;
+        let story_id = request.story_id.trim();
+        if !state.team.story_history.contains_key(story_id) {
+            return Err(create_validation_error(&format!(
+                "No history recorded for story {}",
+                story_id
+            )));
         }
-    }
 
-    /// Gets all branch names
-    fn get_branch_names(&self) -> Vec<String> {
-        self.branches.keys().cloned().collect()
+        let response = state.team.story_history(story_id);
+        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
+
+        Ok(CallToolResult::success(vec![Content::text(
+            response_json.to_string(),
+        )]))
     }
 }
 
-/// Deliberate thinking server implementation
-#[derive(Clone)]
-pub struct DeliberateThinkingServer {
-    state: Arc<Mutex<DeliberateThinkingState>>,
-    tool_router: ToolRouter<Self>,
+/// Arguments for the sprint planning prompt
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SprintPlanningPromptArgs {
+    #[schemars(description = "Goal the sprint should achieve")]
+    pub goal: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Name for the new sprint")]
+    pub sprint_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Comma-separated candidate story ids")]
+    pub story_ids: Option<String>,
+}
+
+/// Arguments for the backlog grooming prompt
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BacklogGroomingPromptArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Comma-separated story ids to groom (whole backlog when omitted)")]
+    pub story_ids: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Theme or label to focus the grooming on")]
+    pub focus: Option<String>,
 }
 
-impl DeliberateThinkingServer {
-    pub fn new() -> Self {
-        Self {
-            state: Arc::new(Mutex::new(DeliberateThinkingState::default())),
-            tool_router: Self::tool_router(),
-        }
-    }
+/// Arguments for the retrospective prompt
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetrospectivePromptArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Sprint to look back on (the latest closed sprint when omitted)")]
+    pub sprint_name: Option<String>,
 }
 
-impl Default for DeliberateThinkingServer {
-    fn default() -> Self {
-        Self::new()
-    }
+/// Arguments for the pre-mortem prompt
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PreMortemPromptArgs {
+    #[schemars(description = "Initiative or release to imagine failing")]
+    pub goal: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Comma-separated story ids in scope")]
+    pub story_ids: Option<String>,
 }
 
-/// Helper function to validate minimum values
-
-fn validate_min_value(field_name: &str, value: u32, min: u32) -> Result<(), McpError> {
-    if value < min {
-        Err(create_validation_error(&format!(
-            "{} must be at least {}",
-            field_name, min
-        )))
-    } else {
-        Ok(())
-    }
+/// Arguments for the five whys prompt
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FiveWhysPromptArgs {
+    #[schemars(description = "Problem or incident to trace to its root cause")]
+    pub problem: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Comma-separated story ids affected by the problem")]
+    pub story_ids: Option<String>,
 }
 
-fn validate_non_empty(field_name: &str, value: &str) -> Result<(), McpError> {
-    if value.trim().is_empty() {
-        Err(create_validation_error(&format!(
-            "{} cannot be empty",
-            field_name
-        )))
-    } else {
-        Ok(())
-    }
+/// Arguments for the design review prompt
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DesignReviewPromptArgs {
+    #[schemars(description = "Design, proposal or document to review")]
+    pub design: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(description = "Comma-separated story ids the design implements")]
+    pub story_ids: Option<String>,
 }
 
-/// Helper function to create validation errors
-fn create_validation_error(message: &str) -> McpError {
-    McpError {
-        code: ErrorCode(-32602),
-        message: message.to_string().into(),
-        data: None,
+/// Describes the stories a prompt is scoped to, from a comma-separated id list
+fn prompt_story_scope(story_ids: Option<&str>, fallback: &str) -> String {
+    let ids = story_ids
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .collect::<Vec<_>>();
+    if ids.is_empty() {
+        fallback.to_string()
+    } else {
+        format!("stories {}", ids.join(", "))
     }
 }
 
-/// Helper function to create serialization errors
-fn create_serialization_error(error: impl std::fmt::Display) -> McpError {
-    McpError {
-        code: ErrorCode(-32603),
-        message: format!("Failed to serialize response: {}", error).into(),
-        data: None,
+/// Builds a facilitation prompt as one user message walking through numbered
+/// `deliberatethinking` calls
+fn facilitation_prompt(intro: String, steps: &[String]) -> Vec<PromptMessage> {
+    let mut text = format!(
+        "{}\n\nRun this as a sequence of `deliberatethinking` calls. Set `role` on every call, \
+         increase `thoughtNumber` each time, and set `nextThoughtNeeded` to false on the last call.\n",
+        intro
+    );
+    for (index, step) in steps.iter().enumerate() {
+        text.push_str(&format!("\n{}. {}", index + 1, step));
     }
+    text.push_str(
+        "\n\nIf the team needs a decision from me, add it to `questions` instead of guessing.",
+    );
+    vec![PromptMessage::new_text(PromptMessageRole::User, text)]
 }
 
-#[tool_router]
+#[prompt_router]
 impl DeliberateThinkingServer {
-    /// Deliberate thinking tool for dynamic and reflective problem-solving
-    #[tool(
-        name = "deliberatethinking",
-        description = "A detailed tool for dynamic and reflective problem-solving through thoughts.
-This tool helps analyze problems through a flexible thinking process that can adapt and evolve.
-Each thought can build on, question, or revise previous insights as understanding deepens.
-
-When to use this tool:
-- Breaking down complex problems into steps
-- Planning and design with room for revision
-- Analysis that might need course correction
-- Problems where the full scope might not be clear initially
-- Problems that require a multi-step solution
-- Tasks that need to maintain context over multiple steps
-- Situations where irrelevant information needs to be filtered out
-
-Key features:
-- You can adjust total_thoughts up or down as you progress
-- You can question or revise previous thoughts
-- You can add more thoughts even after reaching what seemed like the end
-- You can express uncertainty and explore alternative approaches
-- Not every thought needs to build linearly - you can branch or backtrack
-- Generates a solution hypothesis
-- Verifies the hypothesis based on the Chain of Thought steps
-- Repeats the process until satisfied
-- Provides a correct answer"
+    /// Sprint planning session
+    #[prompt(
+        name = "sprintplanning",
+        description = "Plan a sprint towards a goal: estimate, prioritise and commit stories, then start the sprint and vote."
     )]
-
-    pub async fn deliberate_thinking(
+    pub async fn sprint_planning_prompt(
         &self,
-        Parameters(request): Parameters<DeliberateThinkingRequest>,
-    ) -> Result<CallToolResult, McpError> {
-        // Validate parameters
-        request.validate()?;
+        Parameters(args): Parameters<SprintPlanningPromptArgs>,
+    ) -> Vec<PromptMessage> {
+        let sprint_name = args
+            .sprint_name
+            .map(|name| format!("`sprintName` \"{}\"", name))
+            .unwrap_or_else(|| "a `sprintName`".to_string());
+        let scope = prompt_story_scope(args.story_ids.as_deref(), "the top of the backlog");
+        facilitation_prompt(
+            format!("Facilitate sprint planning with the goal: {}.", args.goal),
+            &[
+                format!(
+                    "`projectManager`: restate the goal in `discussionPoints` and review {} (use `querybacklog` to look them up).",
+                    scope
+                ),
+                "`pragmaticProgrammer`: estimate `storyPoints`, set `dependsOn` through `backlogPatches`, and note feasibility concerns in `discussionPoints`.".to_string(),
+                "`productVisionary`: adjust `priority` and add `acceptanceCriteria` through `backlogPatches` so the goal's value is clear.".to_string(),
+                format!(
+                    "`projectManager`: send `startSprint` with {}, the goal, `durationDays`, `participants` with `capacityPoints`, `committedStoryIds` and `risks`.",
+                    sprint_name
+                ),
+                "Each role: cast a `consensusUpdate` vote with `readyForCodeChanges` and a `rationale`.".to_string(),
+            ],
+        )
+    }
 
-        // Convert request to thought data (consumes the request)
-        let thought_data = ThoughtData::from(request.clone());
+    /// Backlog grooming session
+    #[prompt(
+        name = "backloggrooming",
+        description = "Groom the backlog: split, estimate, reprioritise and retire stories."
+    )]
+    pub async fn backlog_grooming_prompt(
+        &self,
+        Parameters(args): Parameters<BacklogGroomingPromptArgs>,
+    ) -> Vec<PromptMessage> {
+        let scope = prompt_story_scope(args.story_ids.as_deref(), "the whole backlog");
+        let focus = args
+            .focus
+            .map(|focus| format!(", focusing on {}", focus))
+            .unwrap_or_default();
+        facilitation_prompt(
+            format!("Facilitate a backlog grooming session for {}{}.", scope, focus),
+            &[
+                "`projectManager`: list the stories under review in `discussionPoints` (narrow them with `querybacklog` filter fields such as `labels`, `status` or `priority`).".to_string(),
+                "`productVisionary`: reprioritise through `backlogPatches` and write missing `acceptanceCriteria`.".to_string(),
+                "`pragmaticProgrammer`: estimate `storyPoints`, record `dependsOn`, and propose splitting oversized stories as new `backlogStories`.".to_string(),
+                "`projectManager`: retire obsolete stories with `removeStoryIds` and summarise the groomed backlog.".to_string(),
+            ],
+        )
+    }
 
-        let mut state = self.state.lock().await;
+    /// Sprint retrospective
+    #[prompt(
+        name = "retrospective",
+        description = "Run a sprint retrospective and turn the findings into tracked action items."
+    )]
+    pub async fn retrospective_prompt(
+        &self,
+        Parameters(args): Parameters<RetrospectivePromptArgs>,
+    ) -> Vec<PromptMessage> {
+        let sprint = args
+            .sprint_name
+            .map(|name| format!("sprint {}", name))
+            .unwrap_or_else(|| "the latest closed sprint".to_string());
+        facilitation_prompt(
+            format!("Facilitate a retrospective for {}.", sprint),
+            &[
+                format!(
+                    "`projectManager`: summarise {} from `sprinthistory` (completed versus incomplete stories and points) in `discussionPoints`.",
+                    sprint
+                ),
+                "`pragmaticProgrammer`: add a `retrospective` entry with `role` `pragmaticProgrammer`, `wentWell`, `toImprove` and `actionItems`.".to_string(),
+                "`productVisionary`: add a `retrospective` entry with `role` `productVisionary`, `wentWell`, `toImprove` and `actionItems`.".to_string(),
+                "`projectManager`: add a `retrospective` entry with `role` `projectManager`, give each action item an `owner`, and close finished actions with `resolveRetroActionIds`.".to_string(),
+            ],
+        )
+    }
 
-        // Update team collaboration state
-        let team_outcome = state.team.process_request(&request);
+    /// Pre-mortem
+    #[prompt(
+        name = "premortem",
+        description = "Imagine an initiative has failed, work back to the causes and record them as risks."
+    )]
+    pub async fn pre_mortem_prompt(
+        &self,
+        Parameters(args): Parameters<PreMortemPromptArgs>,
+    ) -> Vec<PromptMessage> {
+        let scope = prompt_story_scope(args.story_ids.as_deref(), "the active sprint's stories");
+        facilitation_prompt(
+            format!(
+                "Facilitate a pre-mortem for: {}. Assume it has failed badly and explain why. In scope: {}.",
+                args.goal, scope
+            ),
+            &[
+                "`pragmaticProgrammer`: list technical failure causes in `discussionPoints` and add each as a `risks` entry with `likelihood`, `impact` and `storyIds`.".to_string(),
+                "`productVisionary`: list market and user failure causes the same way.".to_string(),
+                "`projectManager`: list delivery failure causes, then set a `mitigation` and `owner` on the highest-exposure risks (see `riskregister`).".to_string(),
+                "Each role: cast a `consensusUpdate` vote on whether the plan survives with those mitigations.".to_string(),
+            ],
+        )
+    }
 
-        // Process the thought based on its type
-        match (
-            &request.branch_from_thought,
-            &request.branch_id,
-            &request.revises_thought,
-        ) {
-            // Branching case
-            (Some(branch_from), Some(branch_id), _) => {
-                state.handle_branching(*branch_from, branch_id.clone(), thought_data);
-            }
-            // Revision case
-            (_, _, Some(revises)) => {
-                state.handle_revision(*revises, thought_data);
-            }
-            // Regular thought case
-            _ => {
-                state.add_thought(thought_data);
-            }
-        }
+    /// Five whys root cause analysis
+    #[prompt(
+        name = "fivewhys",
+        description = "Trace a problem to its root cause by asking why five times, then record the fix."
+    )]
+    pub async fn five_whys_prompt(
+        &self,
+        Parameters(args): Parameters<FiveWhysPromptArgs>,
+    ) -> Vec<PromptMessage> {
+        let scope = prompt_story_scope(args.story_ids.as_deref(), "no particular stories");
+        facilitation_prompt(
+            format!(
+                "Facilitate a five whys analysis of: {}. Affected: {}.",
+                args.problem, scope
+            ),
+            &[
+                "`projectManager`: state the problem and its impact in `discussionPoints`.".to_string(),
+                "Ask \"why?\" five times, one thought each, alternating `pragmaticProgrammer` and `productVisionary`. Each answer becomes the subject of the next why; use `isRevision` and `revisesThought` if an answer turns out wrong.".to_string(),
+                "`projectManager`: record the root cause as a `decisions` entry with the chosen fix, and add follow-up work as `backlogStories`.".to_string(),
+            ],
+        )
+    }
 
-        // Build the project manager report summarising this step
-        let pm_report = state.team.generate_report(&request, &team_outcome);
+    /// Design review
+    #[prompt(
+        name = "designreview",
+        description = "Review a design from feasibility, product and delivery angles and record the outcome as a decision."
+    )]
+    pub async fn design_review_prompt(
+        &self,
+        Parameters(args): Parameters<DesignReviewPromptArgs>,
+    ) -> Vec<PromptMessage> {
+        let scope = prompt_story_scope(args.story_ids.as_deref(), "the stories it touches");
+        facilitation_prompt(
+            format!(
+                "Facilitate a design review of: {}. It implements {}.",
+                args.design, scope
+            ),
+            &[
+                "`pragmaticProgrammer`: assess feasibility, complexity and alternatives in `discussionPoints`, listing anything that must change first.".to_string(),
+                "`productVisionary`: check the design against the stories' `acceptanceCriteria` and user value.".to_string(),
+                "`projectManager`: raise the must-change issues as `blockers`, note delivery risks in `risks`, and record the outcome as a `decisions` entry listing the `options` considered and the `chosenOption`.".to_string(),
+                "Each role: cast a `consensusUpdate` vote on whether the design is ready to implement.".to_string(),
+            ],
+        )
+    }
+}
 
-        // Create response
-        let response = DeliberateThinkingResponse::new(
-            &request,
-            state.get_branch_names(),
-            state.get_history_length(),
-            pm_report,
-        );
+/// Logger name attached to notifications/message
+const LOGGER_NAME: &str = "deliberate-thinking";
 
-        // Log the thought for debugging
-        log_thought_info(&request);
+/// Rank of an MCP logging level, from debug (0) to emergency (7)
+fn logging_severity(level: LoggingLevel) -> u8 {
+    match level {
+        LoggingLevel::Debug => 0,
+        LoggingLevel::Info => 1,
+        LoggingLevel::Notice => 2,
+        LoggingLevel::Warning => 3,
+        LoggingLevel::Error => 4,
+        LoggingLevel::Critical => 5,
+        LoggingLevel::Alert => 6,
+        LoggingLevel::Emergency => 7,
+    }
+}
 
-        // Serialize response
-        let response_json = serde_json::to_value(response).map_err(create_serialization_error)?;
+/// Whether a message at `level` passes the client's minimum level (info when unset)
+fn logging_enabled(minimum: Option<LoggingLevel>, level: LoggingLevel) -> bool {
+    logging_severity(level) >= logging_severity(minimum.unwrap_or(LoggingLevel::Info))
+}
 
-        Ok(CallToolResult::success(vec![Content::text(
-            response_json.to_string(),
-        )]))
+/// Progress notification for a thought, with sprint completion in the message
+fn thought_progress(
+    token: ProgressToken,
+    request: &DeliberateThinkingRequest,
+    sprint_points: Option<&SprintPoints>,
+) -> ProgressNotificationParam {
+    let total = request.total_thoughts.max(request.thought_number);
+    let mut message = format!("Thinking {}/{}", request.thought_number, total);
+    if let Some(points) = sprint_points.filter(|points| points.planned_points > 0) {
+        let percent = u64::from(points.completed_points) * 100 / u64::from(points.planned_points);
+        message.push_str(&format!(
+            "; {}: {}/{} points complete ({}%)",
+            points.sprint_name, points.completed_points, points.planned_points, percent
+        ));
+    }
+    ProgressNotificationParam {
+        progress_token: token,
+        progress: request.thought_number as f64,
+        total: Some(total as f64),
+        message: Some(message),
     }
 }
 
-/// Logs information about the current thought
-fn log_thought_info(request: &DeliberateThinkingRequest) {
-    log::info!(
+/// Lines describing a thought, sent to the client log and optionally to stderr
+fn thought_log_lines(request: &DeliberateThinkingRequest) -> Vec<String> {
+    let mut lines = vec![format!(
         "Deliberate Thinking Step {}/{}: {}",
-        request.thought_number,
-        request.total_thoughts,
-        request.thought
-    );
+        request.thought_number, request.total_thoughts, request.thought
+    )];
 
     if let Some(ref branch_id) = request.branch_id {
-        log::info!("  Branch: {}", branch_id);
+        lines.push(format!("  Branch: {}", branch_id));
     }
 
     if let Some(role) = &request.role {
-        log::info!("  Team role: {}", role);
+        lines.push(format!("  Team role: {}", role));
     }
 
     if request.is_revision.unwrap_or(false) {
         if let Some(revises) = request.revises_thought {
-            log::info!("  Revision of thought {}", revises);
+            lines.push(format!("  Revision of thought {}", revises));
         }
     }
 
@@ -969,41 +6727,58 @@ fn log_thought_info(request: &DeliberateThinkingRequest) {
             .map(|point| format!("{}: {}", point.role, point.detail))
             .collect::<Vec<_>>()
             .join("; ");
-        log::info!("  Discussion points: {}", highlights);
+        lines.push(format!("  Discussion points: {}", highlights));
     }
 
-    if !request.backlog_stories.is_empty() || !request.remove_story_ids.is_empty() {
-        log::info!(
-            "  Backlog updates -> add/update: {}, remove: {}",
+    if !request.backlog_stories.is_empty()
+        || !request.backlog_patches.is_empty()
+        || !request.remove_story_ids.is_empty()
+    {
+        lines.push(format!(
+            "  Backlog updates -> add/update: {}, patch: {}, remove: {}",
             request.backlog_stories.len(),
+            request.backlog_patches.len(),
             request.remove_story_ids.len()
-        );
+        ));
     }
 
     if request.sprint_plan.is_some() {
-        log::info!("  Sprint plan proposal included");
+        lines.push("  Sprint plan proposal included".to_string());
+    }
+
+    if let Some(plan) = &request.start_sprint {
+        lines.push(format!("  Starting sprint {}", plan.sprint_name));
+    }
+
+    if request.close_sprint.is_some() {
+        lines.push("  Closing the active sprint".to_string());
     }
 
     if let Some(consensus) = &request.consensus_update {
-        log::info!(
-            "  Consensus update: ready_for_code_changes={} blockers={}",
+        lines.push(format!(
+            "  Consensus vote: ready_for_code_changes={} blockers={}",
             consensus.ready_for_code_changes,
             consensus.blockers.len()
-        );
+        ));
     }
 
     if let Some(needs_input) = request.requires_user_input {
-        log::info!("  Waiting on user input: {}", needs_input);
+        lines.push(format!("  Waiting on user input: {}", needs_input));
     }
+
+    lines
 }
 
 #[tool_handler]
+#[prompt_handler]
 impl ServerHandler for DeliberateThinkingServer {
     fn get_info(&self) -> InitializeResult {
         InitializeResult {
             protocol_version: ProtocolVersion::V_2024_11_05,
             capabilities: ServerCapabilities {
                 tools: Some(ToolsCapability::default()),
+                prompts: Some(PromptsCapability::default()),
+                logging: Some(JsonObject::default()),
                 ..Default::default()
             },
             server_info: Implementation {
@@ -1016,13 +6791,23 @@ impl ServerHandler for DeliberateThinkingServer {
             instructions: None,
         }
     }
+
+    async fn set_level(
+        &self,
+        request: SetLevelRequestParam,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<(), McpError> {
+        self.state.lock().await.log_level = Some(request.level);
+        Ok(())
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
 
-    let server = DeliberateThinkingServer::new();
+    let config = TeamConfig::from_env()?;
+    let server = DeliberateThinkingServer::with_config(config);
 
     log::info!("Starting Deliberate Thinking MCP Server");
 
@@ -1032,3 +6817,396 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(role: TeamRole, ready: bool) -> ConsensusVote {
+        ConsensusVote {
+            role,
+            ready,
+            rationale: None,
+            thought_number: 1,
+        }
+    }
+
+    #[test]
+    fn unanimous_quorum_needs_every_role() {
+        let rule = QuorumRule::default();
+        let votes = vec![
+            vote(TeamRole::ProjectManager, true),
+            vote(TeamRole::PragmaticProgrammer, true),
+            vote(TeamRole::ProductVisionary, false),
+        ];
+        assert_eq!(
+            rule.evaluate(&votes),
+            (false, vec![TeamRole::ProductVisionary])
+        );
+
+        let votes: Vec<ConsensusVote> = TeamRole::all()
+            .into_iter()
+            .map(|role| vote(role, true))
+            .collect();
+        assert_eq!(rule.evaluate(&votes), (true, Vec::new()));
+    }
+
+    #[test]
+    fn majority_quorum_counts_missing_votes_as_not_ready() {
+        let rule = QuorumRule {
+            mode: QuorumMode::Majority,
+            require_pm_sign_off: false,
+        };
+        let (ready, holding_up) = rule.evaluate(&[
+            vote(TeamRole::PragmaticProgrammer, true),
+            vote(TeamRole::ProductVisionary, true),
+        ]);
+        assert!(ready);
+        assert_eq!(holding_up, vec![TeamRole::ProjectManager]);
+
+        let (ready, _) = rule.evaluate(&[vote(TeamRole::PragmaticProgrammer, true)]);
+        assert!(!ready);
+    }
+
+    #[test]
+    fn pm_sign_off_overrides_a_met_majority() {
+        let rule = QuorumRule {
+            mode: QuorumMode::Majority,
+            require_pm_sign_off: true,
+        };
+        let (ready, _) = rule.evaluate(&[
+            vote(TeamRole::ProjectManager, false),
+            vote(TeamRole::PragmaticProgrammer, true),
+            vote(TeamRole::ProductVisionary, true),
+        ]);
+        assert!(!ready);
+
+        let (ready, _) = rule.evaluate(&[
+            vote(TeamRole::ProjectManager, true),
+            vote(TeamRole::PragmaticProgrammer, true),
+        ]);
+        assert!(ready);
+    }
+
+    fn item(id: &str, depends_on: &[&str], parent_id: Option<&str>) -> BacklogItem {
+        BacklogItem {
+            id: id.to_string(),
+            title: format!("Story {}", id),
+            depends_on: depends_on.iter().map(|id| id.to_string()).collect(),
+            parent_id: parent_id.map(str::to_string),
+            ..BacklogItem::default()
+        }
+    }
+
+    fn backlog_of(items: Vec<BacklogItem>) -> HashMap<String, BacklogItem> {
+        items
+            .into_iter()
+            .map(|item| (item.id.clone(), item))
+            .collect()
+    }
+
+    #[test]
+    fn dependency_cycle_is_reported_from_its_first_id() {
+        let backlog = backlog_of(vec![
+            item("A", &["B"], None),
+            item("B", &["C"], None),
+            item("C", &["A"], None),
+            item("D", &["A"], None),
+        ]);
+        assert_eq!(
+            find_dependency_cycle(&backlog),
+            Some(vec!["A".into(), "B".into(), "C".into(), "A".into()])
+        );
+    }
+
+    #[test]
+    fn shared_dependencies_are_not_a_cycle() {
+        let backlog = backlog_of(vec![
+            item("A", &["B", "C"], None),
+            item("B", &["C"], None),
+            item("C", &["MISSING"], None),
+        ]);
+        assert_eq!(find_dependency_cycle(&backlog), None);
+    }
+
+    #[test]
+    fn self_dependency_is_a_cycle() {
+        let backlog = backlog_of(vec![item("A", &["A"], None)]);
+        assert_eq!(
+            find_dependency_cycle(&backlog),
+            Some(vec!["A".into(), "A".into()])
+        );
+    }
+
+    #[test]
+    fn default_workflow_requires_a_reason_to_block_or_reopen() {
+        let workflow = WorkflowConfig::default();
+        let mut story = item("A", &[], None);
+        story.status = StoryStatus::Blocked;
+        assert_eq!(
+            workflow.violation(&StoryStatus::InProgress, &story),
+            Some("A cannot move In Progress -> Blocked without a statusReason".into())
+        );
+
+        story.status_reason = Some("  ".into());
+        assert!(workflow
+            .violation(&StoryStatus::InProgress, &story)
+            .is_some());
+
+        story.status_reason = Some("Waiting on the API team".into());
+        assert_eq!(workflow.violation(&StoryStatus::InProgress, &story), None);
+
+        let mut reopened = item("B", &[], None);
+        reopened.status = StoryStatus::Todo;
+        assert!(workflow.violation(&StoryStatus::Done, &reopened).is_some());
+    }
+
+    #[test]
+    fn workflow_rejects_unlisted_transitions_but_not_unchanged_status() {
+        let workflow = WorkflowConfig {
+            transitions: vec![StatusTransition {
+                from: StoryStatus::Todo,
+                to: StoryStatus::InProgress,
+                requires_reason: false,
+            }],
+            enforcement: WorkflowEnforcement::Reject,
+        };
+        let mut story = item("A", &[], None);
+        story.status = StoryStatus::InProgress;
+        assert_eq!(workflow.violation(&StoryStatus::Todo, &story), None);
+        assert_eq!(workflow.violation(&StoryStatus::InProgress, &story), None);
+
+        story.status = StoryStatus::Done;
+        assert_eq!(
+            workflow.violation(&StoryStatus::Todo, &story),
+            Some("A cannot move To Do -> Done: transition not allowed by the workflow".into())
+        );
+    }
+
+    #[test]
+    fn parent_cycle_is_reported_with_its_start_repeated() {
+        let backlog = backlog_of(vec![
+            item("EPIC", &[], Some("B")),
+            item("B", &[], Some("C")),
+            item("C", &[], Some("B")),
+        ]);
+        assert_eq!(
+            find_parent_cycle(&backlog),
+            Some(vec!["B".into(), "C".into(), "B".into()])
+        );
+    }
+
+    #[test]
+    fn parent_chains_ending_at_a_root_or_unknown_id_are_not_cycles() {
+        let backlog = backlog_of(vec![
+            item("EPIC", &[], None),
+            item("FEATURE", &[], Some("EPIC")),
+            item("TASK", &[], Some("FEATURE")),
+            item("ORPHAN", &[], Some("MISSING")),
+        ]);
+        assert_eq!(find_parent_cycle(&backlog), None);
+    }
+
+    #[test]
+    fn csv_records_honour_quoted_commas_quotes_and_newlines() {
+        let content = "id,title,notes\r\nA,\"Login, then logout\",\"Say \"\"hi\"\"\nfirst\"\r\n\r\n,,\nB,Plain,\n";
+        let records = parse_csv_records(content).unwrap();
+        assert_eq!(
+            records,
+            vec![
+                vec!["id", "title", "notes"],
+                vec!["A", "Login, then logout", "Say \"hi\"\nfirst"],
+                vec!["B", "Plain", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_records_keep_a_last_line_without_newline() {
+        let records = parse_csv_records("id\nA").unwrap();
+        assert_eq!(records, vec![vec!["id"], vec!["A"]]);
+        assert!(parse_csv_records("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn csv_records_reject_an_unterminated_quote() {
+        let error = parse_csv_records("id,title\nA,\"never closed\n").unwrap_err();
+        assert!(error.message.contains("unterminated"));
+    }
+
+    #[test]
+    fn csv_export_round_trips_through_import() {
+        let mut full = item("A-1", &["B-2", "C-3"], Some("EPIC"));
+        full.title = "Sign in, then \"remember me\"".into();
+        full.priority = PriorityLevel::High;
+        full.status = StoryStatus::Blocked;
+        full.owner = Some(TeamRole::PragmaticProgrammer);
+        full.notes = Some("Two lines\nof notes".into());
+        full.story_points = Some(5);
+        full.labels = vec!["auth".into(), "web".into()];
+        full.item_type = ItemType::Bug;
+        full.status_reason = Some("Waiting on SSO keys".into());
+        let bare = item("B-2", &[], None);
+
+        let request = BacklogImportRequest {
+            format: ImportFormat::Csv,
+            content: export_csv(&[full.clone(), bare.clone()]),
+            columns: HashMap::new(),
+            mode: ImportMode::Merge,
+            dry_run: false,
+            role: None,
+            thought_number: None,
+        };
+        let (imported, duplicate_ids) = request.parse(&HashMap::new()).unwrap();
+        assert!(duplicate_ids.is_empty());
+        assert_eq!(imported.len(), 2);
+        for (original, imported) in [full, bare].iter().zip(&imported) {
+            for field in CSV_IMPORT_FIELDS {
+                assert_eq!(
+                    exported_field(imported, field),
+                    exported_field(original, field),
+                    "{} lost {} in the round trip",
+                    original.id,
+                    field
+                );
+            }
+        }
+    }
+
+    fn thought(number: u32, text: &str) -> DeliberateThinkingRequest {
+        serde_json::from_value(serde_json::json!({
+            "thought": text,
+            "nextThoughtNeeded": true,
+            "thoughtNumber": number,
+            "totalThoughts": 3,
+            "requiresUserInput": true,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn elicited_thought_answer_is_not_filed_under_a_question_queued_meanwhile() {
+        let mut team = TeamState::default();
+        let request = thought(1, "Which database?");
+        let form = team.request_user_input(&request, true).unwrap();
+
+        let other = QuestionInput {
+            question: "Which region?".into(),
+            options: Vec::new(),
+            blocking: true,
+        };
+        let other_id = team.queue_question(&thought(2, "Regions"), &other);
+
+        let mut outcome = TeamUpdateOutcome::default();
+        let content = serde_json::json!({ THOUGHT_ANSWER_KEY: "Postgres" });
+        team.record_elicited_answers(&request, &form, &content, &mut outcome);
+
+        assert!(outcome.elicited_answers.is_empty());
+        assert_eq!(outcome.warnings.len(), 1);
+        let other = team.questions.iter().find(|q| q.id == other_id).unwrap();
+        assert_eq!(other.answer, None);
+    }
+
+    #[test]
+    fn elicited_answers_overtaken_by_another_call_are_warned_about() {
+        let mut team = TeamState::default();
+        let request = thought(1, "Which database?");
+        team.queue_thought_question(&request).unwrap();
+        let form = team.request_user_input(&request, true).unwrap();
+        team.answer_question(&AnswerQuestionRequest {
+            question_id: "Q-1".into(),
+            answer: "MySQL".into(),
+        })
+        .unwrap();
+
+        let mut outcome = TeamUpdateOutcome::default();
+        let content = serde_json::json!({ "Q-1": "Postgres" });
+        team.record_elicited_answers(&request, &form, &content, &mut outcome);
+
+        assert!(outcome.elicited_answers.is_empty());
+        assert_eq!(
+            outcome.warnings,
+            vec!["Elicited answer not recorded: questionId: Q-1 has already been answered"]
+        );
+        assert_eq!(team.questions[0].answer.as_deref(), Some("MySQL"));
+    }
+
+    #[test]
+    fn sprint_points_saturate_instead_of_overflowing() {
+        let mut team = TeamState::default();
+        for id in ["A", "B"] {
+            let mut story = item(id, &[], None);
+            story.story_points = Some(u32::MAX - 1);
+            story.status = StoryStatus::Done;
+            team.backlog.insert(id.to_string(), story);
+        }
+        let plan: SprintPlan = serde_json::from_value(serde_json::json!({
+            "sprintName": "Sprint 1",
+            "goal": "Ship",
+            "durationDays": 10,
+            "committedStoryIds": ["A", "B"],
+            "participants": [
+                { "role": "projectManager", "capacityPoints": u32::MAX },
+                { "role": "pragmaticProgrammer", "capacityPoints": 1 },
+            ],
+        }))
+        .unwrap();
+
+        let points = team.sprint_points(&plan);
+        assert_eq!(points.planned_points, u32::MAX);
+        assert_eq!(points.completed_points, u32::MAX);
+        assert_eq!(points.capacity_points, Some(u32::MAX));
+    }
+
+    #[test]
+    fn ascii_bars_scale_large_values_without_overflow() {
+        let rows = vec![
+            ("S1".to_string(), u32::MAX),
+            ("S2".to_string(), u32::MAX / 2),
+        ];
+        let chart = ascii_bars(&rows, u32::MAX);
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines[0].matches('#').count(), 20);
+        assert_eq!(lines[1].matches('#').count(), 10);
+    }
+
+    #[test]
+    fn epic_progress_handles_huge_estimates() {
+        let mut team = TeamState::default();
+        let mut done = item("A", &[], Some("EPIC"));
+        done.story_points = Some(u32::MAX / 2);
+        done.status = StoryStatus::Done;
+        let mut todo = item("B", &[], Some("EPIC"));
+        todo.story_points = Some(u32::MAX / 2);
+        for story in [item("EPIC", &[], None), done, todo] {
+            team.backlog.insert(story.id.clone(), story);
+        }
+
+        let progress = team.progress("EPIC").unwrap();
+        assert_eq!(progress.total_points, u32::MAX - 1);
+        assert_eq!(progress.percent_complete, 50);
+    }
+
+    #[test]
+    fn decision_file_names_fall_back_when_the_title_has_no_slug() {
+        let record = |id: &str, title: &str| DecisionRecord {
+            id: id.into(),
+            title: title.into(),
+            context: "Context".into(),
+            options: Vec::new(),
+            chosen_option: "Option".into(),
+            consequences: Vec::new(),
+            deciding_roles: Vec::new(),
+            thought_number: 1,
+            decided_at: Utc::now(),
+            status: DecisionStatus::Accepted,
+            supersedes: None,
+            superseded_by: None,
+        };
+        assert_eq!(
+            record("ADR-0003", "Store sessions in Redis!").file_name(),
+            "0003-store-sessions-in-redis.md"
+        );
+        assert_eq!(record("ADR-0004", "???").file_name(), "0004-decision.md");
+    }
+}